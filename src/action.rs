@@ -1,12 +1,9 @@
-use std::{fmt, string::ToString};
+use std::{path::PathBuf, time::Duration};
 
-use serde::{
-    Deserialize, Serialize,
-    de::{self, Deserializer, Visitor},
-};
+use serde::{Deserialize, Serialize};
 use strum::Display;
 
-//// ANCHOR: action_enum
+// ANCHOR: action_enum
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Display, Deserialize)]
 pub enum Action {
     Tick,
@@ -15,20 +12,158 @@ pub enum Action {
     Suspend,
     Resume,
     Quit,
+    RequestQuit,
+    CancelQuit,
     Refresh,
+    /// Clears the history, asking first if `Home::confirm_clear_history` is set.
+    RequestClearHistory,
+    CancelClearHistory,
+    ClearHistory,
+    /// A warning, shorthand for `Notify(Severity::Warn, _)`.
     Error(String),
+    Notify(Severity, String),
+    /// Opens the help, leaving it open if it already is.
     Help,
     ToggleShowHelp,
+    ToggleErrors,
+    ToggleWrap,
+    /// Gives the input and history pane a bigger share of the screen.
+    GrowInput,
+    ShrinkInput,
+    /// Ignores every action that would change the counter, history or zones.
+    ToggleReadOnly,
+    ToggleActionCounts,
+    ToggleRecentActions,
+    /// Shows how long each of the last frames took, to spot lag.
+    ToggleFrameTimes,
+    CycleTheme,
+    /// Overrides the theme's main panel text color with the next of a few presets.
+    CycleTextColor,
+    DumpState,
+    /// Writes every following action to the file, for `Home::replay`.
+    StartRecording(PathBuf),
+    StopRecording,
+    /// Sends the actions recorded in the file again, see `Home::replay`.
+    Replay(PathBuf),
+    /// Moves focus to the next pane, muting the highlight of the others.
+    CycleFocus,
+    NextTab,
+    PrevTab,
+    /// Selects the tab at this 1-based position, if there is one.
+    SelectTab(usize),
+    EditZoneItem,
+    SelectNextItem,
+    SelectPrevItem,
+    AddZone,
+    InsertZoneAfter(usize),
+    RemoveZone(usize),
+    RemoveSelectedZone,
+    ToggleZoneEnabled(usize),
+    ToggleSelectedZoneEnabled,
+    NextZone,
+    PrevZone,
+    /// Selects the first zone with this name, ignoring case.
+    GotoZone(String),
+    Save,
+    Load,
     IncrementSingle,
     DecrementSingle,
     ScheduleIncrement,
     ScheduleDecrement,
+    /// Changes the time between app ticks.
+    SetTickRate(Duration),
     Increment(usize),
     Decrement(usize),
+    SetCounter(usize),
+    /// Changes how far `IncrementSingle` and `DecrementSingle` move the counter.
+    SetStep(usize),
     CompleteInput(String),
+    /// The result of checking an input, which is added to the history if it passed.
+    InputValidated(bool, String),
+    EmptyInput,
+    DeleteSelected,
+    MoveEntryUp,
+    MoveEntryDown,
+    CopySelected,
+    EnterFilter,
+    UpdateFilter(String),
+    ClearFilter,
+    Undo,
+    Redo,
+    GotoTop,
+    GotoBottom,
+    ScrollHistoryUp,
+    ScrollHistoryDown,
+    /// Moves the history selection by as many entries as the list shows at once.
+    PageUpList,
+    PageDownList,
+    /// Scrolls the main panel, where `ScrollHistoryUp` scrolls the history list.
+    ScrollUp,
+    ScrollDown,
     EnterNormal,
     EnterInsert,
     EnterProcessing,
     ExitProcessing,
     Update,
 }
+
+/// Merges each run of consecutive `Increment`s, or of `Decrement`s, into one action
+/// moving the counter by their total, leaving everything else as it was.
+///
+/// Only runs in one direction are merged, as the counter saturates at zero, so going
+/// down and then up is not the same as going by the difference.
+pub fn coalesce(actions: impl IntoIterator<Item = Action>) -> Vec<Action> {
+    let mut coalesced: Vec<Action> = Vec::new();
+    for action in actions {
+        match (coalesced.last_mut(), action) {
+            (Some(Action::Increment(total)), Action::Increment(i))
+            | (Some(Action::Decrement(total)), Action::Decrement(i)) => {
+                *total = total.saturating_add(i);
+            }
+            (_, action) => coalesced.push(action),
+        }
+    }
+    coalesced
+}
+
+/// How serious a status line message is, which sets its color and how long it stays.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Display, Deserialize,
+)]
+pub enum Severity {
+    Info,
+    #[default]
+    Warn,
+    Error,
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_coalesce_merges_counter_changes() {
+        assert_eq!(
+            coalesce(vec![Action::Increment(1); 3]),
+            [Action::Increment(3)]
+        );
+        assert_eq!(
+            coalesce([
+                Action::Decrement(1),
+                Action::Decrement(2),
+                Action::Increment(1),
+                Action::Tick,
+                Action::Increment(1),
+                Action::Increment(usize::MAX),
+            ]),
+            [
+                Action::Decrement(3),
+                Action::Increment(1),
+                Action::Tick,
+                Action::Increment(usize::MAX),
+            ]
+        );
+    }
+}
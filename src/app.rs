@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use color_eyre::eyre::Result;
 use crossterm::event::KeyEvent;
 use ratatui::prelude::Rect;
@@ -5,12 +7,20 @@ use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 
 use crate::{
-    action::Action,
-    components::{Component, fps::FpsCounter, home::Home},
-    config::Config,
+    action::{Action, coalesce},
+    components::{
+        Component,
+        fps::FpsCounter,
+        home::{Home, PERSIST_FILE},
+    },
+    config::{Config, load_user_keymap},
     tui,
+    utils::{get_config_dir, get_data_dir},
 };
 
+/// Longest input the app accepts into the history.
+const MAX_INPUT_LEN: usize = 80;
+
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Mode {
     #[default]
@@ -26,11 +36,22 @@ pub struct App {
     pub should_suspend: bool,
     pub mode: Mode,
     pub last_tick_key_events: Vec<KeyEvent>,
+    /// Whether queued actions go through `coalesce` before being handled.
+    pub coalesce_actions: bool,
+    /// Where to listen for actions sent by other programs, if anywhere.
+    #[cfg(feature = "control-socket")]
+    pub control_socket: Option<std::path::PathBuf>,
 }
 
 impl App {
     pub fn new(tick_rate: f64, frame_rate: f64) -> Result<Self> {
-        let home = Home::new();
+        let home = Home::new()
+            .keymap(load_user_keymap(&get_config_dir())?)
+            .confirm_quit(true)
+            .confirm_clear_history(true)
+            .validate_input(|input| input.chars().count() <= MAX_INPUT_LEN)
+            .tick_rate(Duration::from_secs_f64(1.0 / tick_rate))
+            .load_persisted(get_data_dir().join(PERSIST_FILE));
         let fps = FpsCounter::new();
         let config = Config::new()?;
         let mode = Mode::Home;
@@ -43,15 +64,40 @@ impl App {
             config,
             mode,
             last_tick_key_events: Vec::new(),
+            coalesce_actions: false,
+            #[cfg(feature = "control-socket")]
+            control_socket: None,
         })
     }
 
+    /// Merges runs of queued counter changes before handling them, see `coalesce`.
+    pub fn coalesce_actions(mut self, coalesce_actions: bool) -> Self {
+        self.coalesce_actions = coalesce_actions;
+        self
+    }
+
+    /// Listens for newline-delimited JSON actions on a Unix socket at `path` while
+    /// running, so scripts can drive the app with e.g. `echo '"Quit"' | nc -U path`.
+    #[cfg(feature = "control-socket")]
+    pub fn control_socket(mut self, path: std::path::PathBuf) -> Self {
+        self.control_socket = Some(path);
+        self
+    }
+
     pub async fn run(&mut self) -> Result<()> {
         let (action_tx, mut action_rx) = mpsc::unbounded_channel();
+        #[cfg(feature = "control-socket")]
+        // Removes the socket again however `run` returns
+        let _control = match &self.control_socket {
+            Some(path) => Some(crate::control::listen(path, action_tx.clone())?),
+            None => None,
+        };
 
         let mut tui = tui::Tui::new()?;
         tui.tick_rate(self.tick_rate);
         tui.frame_rate(self.frame_rate);
+        tui.mouse(true);
+        tui.paste(true);
         tui.enter()?;
 
         for component in self.components.iter_mut() {
@@ -68,14 +114,22 @@ impl App {
 
         loop {
             if let Some(e) = tui.next().await {
+                // Components get the first say, the config bindings are only a fallback
+                let mut handled = false;
+                for component in self.components.iter_mut() {
+                    if let Some(action) = component.handle_events(Some(e.clone()))? {
+                        action_tx.send(action)?;
+                        handled = true;
+                    }
+                }
                 match e {
                     tui::Event::Quit => action_tx.send(Action::Quit)?,
                     tui::Event::Tick => action_tx.send(Action::Tick)?,
                     tui::Event::Render => action_tx.send(Action::Render)?,
                     tui::Event::Resize(x, y) => action_tx.send(Action::Resize(x, y))?,
-                    tui::Event::Key(key) => {
+                    tui::Event::Key(key) if !handled => {
                         if let Some(keymap) = self.config.keybindings.get(&self.mode) {
-                            if let Some(action) = keymap.get(&vec![key.clone()]) {
+                            if let Some(action) = keymap.get(&vec![key]) {
                                 log::info!("Got action: {action:?}");
                                 action_tx.send(action.clone())?;
                             } else {
@@ -93,55 +147,66 @@ impl App {
                     }
                     _ => {}
                 }
-                for component in self.components.iter_mut() {
-                    if let Some(action) = component.handle_events(Some(e.clone()))? {
-                        action_tx.send(action)?;
-                    }
-                }
             }
 
-            while let Ok(action) = action_rx.try_recv() {
-                if action != Action::Tick && action != Action::Render {
-                    log::debug!("{action:?}");
+            loop {
+                let mut actions: Vec<Action> =
+                    std::iter::from_fn(|| action_rx.try_recv().ok()).collect();
+                if actions.is_empty() {
+                    break;
                 }
-                match action {
-                    Action::Tick => {
-                        self.last_tick_key_events.drain(..);
+                if self.coalesce_actions {
+                    actions = coalesce(actions);
+                }
+                for action in actions {
+                    if action != Action::Tick && action != Action::Render {
+                        log::debug!("{action:?}");
                     }
-                    Action::Quit => self.should_quit = true,
-                    Action::Suspend => self.should_suspend = true,
-                    Action::Resume => self.should_suspend = false,
-                    Action::Resize(w, h) => {
-                        tui.resize(Rect::new(0, 0, w, h))?;
-                        tui.draw(|f| {
-                            for component in self.components.iter_mut() {
-                                let r = component.draw(f, f.area());
-                                if let Err(e) = r {
-                                    action_tx
-                                        .send(Action::Error(format!("Failed to draw: {:?}", e)))
-                                        .unwrap();
+                    match action {
+                        Action::Tick => {
+                            self.last_tick_key_events.drain(..);
+                        }
+                        Action::Quit => self.should_quit = true,
+                        Action::Suspend => self.should_suspend = true,
+                        Action::Resume => self.should_suspend = false,
+                        Action::SetTickRate(tick_rate) if !tick_rate.is_zero() => {
+                            // The event loop reads the rate when it starts, so restart it
+                            self.tick_rate = 1.0 / tick_rate.as_secs_f64();
+                            tui.tick_rate(self.tick_rate);
+                            tui.start();
+                        }
+                        Action::Resize(w, h) => {
+                            tui.resize(Rect::new(0, 0, w, h))?;
+                            tui.draw(|f| {
+                                for component in self.components.iter_mut() {
+                                    let r = component.draw(f, f.area());
+                                    if let Err(e) = r {
+                                        action_tx
+                                            .send(Action::Error(format!("Failed to draw: {:?}", e)))
+                                            .unwrap();
+                                    }
                                 }
-                            }
-                        })?;
-                    }
-                    Action::Render => {
-                        tui.draw(|f| {
-                            for component in self.components.iter_mut() {
-                                let r = component.draw(f, f.area());
-                                if let Err(e) = r {
-                                    action_tx
-                                        .send(Action::Error(format!("Failed to draw: {:?}", e)))
-                                        .unwrap();
+                            })?;
+                        }
+                        Action::Render if self.components.iter().any(|c| c.is_dirty()) => {
+                            tui.draw(|f| {
+                                for component in self.components.iter_mut() {
+                                    let r = component.draw(f, f.area());
+                                    if let Err(e) = r {
+                                        action_tx
+                                            .send(Action::Error(format!("Failed to draw: {:?}", e)))
+                                            .unwrap();
+                                    }
                                 }
-                            }
-                        })?;
+                            })?;
+                        }
+                        _ => {}
+                    }
+                    for component in self.components.iter_mut() {
+                        if let Some(action) = component.update(action.clone())? {
+                            action_tx.send(action)?
+                        };
                     }
-                    _ => {}
-                }
-                for component in self.components.iter_mut() {
-                    if let Some(action) = component.update(action.clone())? {
-                        action_tx.send(action)?
-                    };
                 }
             }
             if self.should_suspend {
@@ -150,6 +215,8 @@ impl App {
                 tui = tui::Tui::new()?;
                 tui.tick_rate(self.tick_rate);
                 tui.frame_rate(self.frame_rate);
+                tui.mouse(true);
+                tui.paste(true);
                 tui.enter()?;
             } else if self.should_quit {
                 tui.stop()?;
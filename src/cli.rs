@@ -1,5 +1,3 @@
-use std::path::PathBuf;
-
 use clap::Parser;
 
 use crate::utils::version;
@@ -24,4 +22,12 @@ pub struct Cli {
         default_value_t = 60.0
     )]
     pub frame_rate: f64,
+
+    #[cfg(feature = "control-socket")]
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Unix socket to read actions from, one JSON action per line"
+    )]
+    pub control_socket: Option<std::path::PathBuf>,
 }
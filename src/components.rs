@@ -1,6 +1,11 @@
 use color_eyre::eyre::Result;
 use crossterm::event::{KeyEvent, MouseEvent};
-use ratatui::layout::Rect;
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Rect, Size},
+    style::Style,
+    text::Line,
+    widgets::{Block, Clear, Widget},
+};
 use tokio::sync::mpsc::UnboundedSender;
 
 use crate::{
@@ -12,7 +17,7 @@ use crate::{
 pub mod fps;
 pub mod home;
 
-//// ANCHOR: component
+// ANCHOR: component
 pub trait Component {
     #[allow(unused_variables)]
     fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
@@ -29,6 +34,7 @@ pub trait Component {
         let r = match event {
             Some(Event::Key(key_event)) => self.handle_key_events(key_event)?,
             Some(Event::Mouse(mouse_event)) => self.handle_mouse_events(mouse_event)?,
+            Some(Event::Paste(text)) => self.handle_paste(text)?,
             _ => None,
         };
         Ok(r)
@@ -42,9 +48,62 @@ pub trait Component {
         Ok(None)
     }
     #[allow(unused_variables)]
+    fn handle_paste(&mut self, text: String) -> Result<Option<Action>> {
+        Ok(None)
+    }
+    #[allow(unused_variables)]
     fn update(&mut self, action: Action) -> Result<Option<Action>> {
         Ok(None)
     }
+    /// Whether the component has changed since it was last drawn.
+    ///
+    /// The app skips a render frame when no component is dirty.
+    fn is_dirty(&self) -> bool {
+        true
+    }
     fn draw(&mut self, f: &mut Frame<'_>, rect: Rect) -> Result<()>;
 }
-//// ANCHOR_END: component
+// ANCHOR_END: component
+
+/// Returns a rect centered in `area` taking up the given percentages of its size.
+pub fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Percentage(percent_x)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Percentage(percent_y)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
+
+/// Returns a `width` x `height` rect centered in `area`, shrunk to fit if needed.
+pub fn centered_area(area: Rect, width: u16, height: u16) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
+
+/// Draws `body` in a bordered popup titled `title`, centered in `area`.
+///
+/// The popup is sized to fit a `body_size` body and its title, but stays within the
+/// middle 80% of `area`.
+pub fn render_modal(
+    f: &mut Frame<'_>,
+    area: Rect,
+    title: Line<'_>,
+    border_style: Style,
+    body_size: Size,
+    body: impl Widget,
+) {
+    let width = body_size.width.max(title.width() as u16) + 2;
+    let popup = centered_area(centered_rect(80, 80, area), width, body_size.height + 2);
+    let block = Block::bordered().title(title).border_style(border_style);
+    let body_area = block.inner(popup);
+    f.render_widget(Clear, popup);
+    f.render_widget(block, popup);
+    f.render_widget(body, body_area);
+}
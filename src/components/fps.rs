@@ -21,6 +21,8 @@ pub struct FpsCounter {
     render_start_time: Instant,
     render_frames: u32,
     render_fps: f64,
+
+    needs_render: bool,
 }
 
 impl Default for FpsCounter {
@@ -38,6 +40,7 @@ impl FpsCounter {
             render_start_time: Instant::now(),
             render_frames: 0,
             render_fps: 0.0,
+            needs_render: true,
         }
     }
 
@@ -49,6 +52,7 @@ impl FpsCounter {
             self.app_fps = self.app_frames as f64 / elapsed;
             self.app_start_time = now;
             self.app_frames = 0;
+            self.needs_render = true;
         }
         Ok(())
     }
@@ -61,6 +65,7 @@ impl FpsCounter {
             self.render_fps = self.render_frames as f64 / elapsed;
             self.render_start_time = now;
             self.render_frames = 0;
+            self.needs_render = true;
         }
         Ok(())
     }
@@ -77,7 +82,13 @@ impl Component for FpsCounter {
         Ok(None)
     }
 
+    fn is_dirty(&self) -> bool {
+        // The rates are worked out again once a second, and need drawing when they are
+        self.needs_render
+    }
+
     fn draw(&mut self, f: &mut Frame<'_>, rect: Rect) -> Result<()> {
+        self.needs_render = false;
         let rects = Layout::default()
             .direction(Direction::Vertical)
             .constraints(vec![
@@ -1,16 +1,38 @@
-use color_eyre::eyre::Result;
-use crossterm::event::{KeyCode, KeyEvent};
+use color_eyre::eyre::{Result, eyre};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use log::error;
 use ratatui::{prelude::*, widgets::*};
-use std::{collections::HashMap, fmt::Display, time::Duration};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 use strum::{Display, EnumIter, FromRepr, IntoEnumIterator};
 use style::palette::tailwind;
-use tokio::sync::mpsc::UnboundedSender;
-use tracing::{Instrument, trace};
-use tui_input::{Input, backend::crossterm::EventHandler};
+use tokio::{sync::mpsc::UnboundedSender, task::AbortHandle};
+use tui_input::{Input, InputRequest, backend::crossterm::EventHandler};
 
-use super::{Component, Frame};
-use crate::{action::Action, config::key_event_to_string};
+use super::{Component, Frame, centered_area, render_modal};
+use crate::{
+    action::{Action, Severity},
+    app,
+    config::{Config, Theme, key_event_to_string, parse_palette},
+    tui::Event,
+    utils::get_data_dir,
+};
+
+#[cfg(test)]
+pub(crate) mod harness;
+mod tabs;
+
+pub use tabs::{
+    IoPoint, IoWidget, IoWidgetState, MiscWidget, MiscWidgetState, Motor, MotorWidget,
+    MotorWidgetState, Sensor, SensorWidget, SensorWidgetState,
+};
 
 #[derive(Default, Clone, Copy, Display, FromRepr, EnumIter)]
 enum ItemMode {
@@ -18,16 +40,42 @@ enum ItemMode {
     #[strum(to_string = "Normal")]
     Normal,
     #[strum(to_string = "Insert")]
-    Insert(i32),
+    Insert,
     #[strum(to_string = "Selected")]
     Selected(i32, i32),
 }
 
-#[derive(Clone, Debug)]
+/// What an edited field holds, which decides the keys it takes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum InputKind {
+    #[default]
+    Text,
+    /// Whole numbers, possibly negative.
+    Numeric,
+}
+
+impl InputKind {
+    /// Whether `c` can be typed into a field of this kind.
+    pub fn accepts(self, c: char) -> bool {
+        match self {
+            Self::Text => true,
+            Self::Numeric => c.is_ascii_digit() || c == '-',
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Zone {
     name: String,
     prev_zone: i32,
     next_zone: i32,
+    /// Disabled zones are bypassed when following links.
+    #[serde(default = "enabled_by_default")]
+    enabled: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
 }
 
 impl Default for Zone {
@@ -36,8 +84,74 @@ impl Default for Zone {
             name: "Zone".to_owned(),
             prev_zone: -1,
             next_zone: -1,
+            enabled: true,
+        }
+    }
+}
+
+impl Zone {
+    /// An enabled zone linked to the zones at the given indices, `-1` for none.
+    pub fn new(name: impl Into<String>, prev_zone: i32, next_zone: i32) -> Self {
+        Self {
+            name: name.into(),
+            prev_zone,
+            next_zone,
+            enabled: true,
+        }
+    }
+
+    pub fn value(&self, item: ZoneItem) -> String {
+        match item {
+            ZoneItem::Name => self.name.clone(),
+            ZoneItem::UpstreamZone => format!("{}", self.prev_zone),
+            ZoneItem::DownstreamZone => format!("{}", self.next_zone),
+        }
+    }
+
+    /// Sets the field behind `item` from its edited text.
+    ///
+    /// Link fields must parse as an `i32`; empty input and any negative number
+    /// clear the link to `-1`. On error the zone is left unchanged.
+    pub fn set_value(&mut self, item: ZoneItem, value: &str) -> Result<(), String> {
+        match item {
+            // The name is also the block title, which would vanish if it were blank
+            ZoneItem::Name if value.trim().is_empty() => {
+                return Err("Zone name can't be empty".to_owned());
+            }
+            ZoneItem::Name => self.name = value.to_owned(),
+            ZoneItem::UpstreamZone => self.prev_zone = parse_link(value, "Upstream zone")?,
+            ZoneItem::DownstreamZone => self.next_zone = parse_link(value, "Downstream zone")?,
+        }
+        Ok(())
+    }
+}
+
+/// Checks that every link in `zones` is either `-1` or a valid index.
+pub fn validate_links(zones: &[Zone]) -> Result<()> {
+    for (i, zone) in zones.iter().enumerate() {
+        for link in [zone.prev_zone, zone.next_zone] {
+            if link != -1 && usize::try_from(link).map_or(true, |l| l >= zones.len()) {
+                return Err(eyre!(
+                    "Zone {} ({}) links to missing zone {}",
+                    i,
+                    zone.name,
+                    link
+                ));
+            }
         }
     }
+    Ok(())
+}
+
+fn parse_link(value: &str, field: &str) -> Result<i32, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(-1);
+    }
+    value
+        .parse::<i32>()
+        .map(|link| link.max(-1))
+        .map_err(|_| format!("{field} must be a number"))
 }
 
 impl Display for Zone {
@@ -50,8 +164,8 @@ impl Display for Zone {
     }
 }
 
-#[derive(Clone, Copy, Display, FromRepr, EnumIter)]
-enum ZoneItem {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Display, FromRepr, EnumIter)]
+pub enum ZoneItem {
     #[strum(to_string = "Name")]
     Name,
     #[strum(to_string = "Upstream Zone")]
@@ -60,58 +174,317 @@ enum ZoneItem {
     DownstreamZone,
 }
 
+impl ZoneItem {
+    /// Links are zone indices, so only the name takes free text.
+    pub fn input_kind(self) -> InputKind {
+        match self {
+            Self::Name => InputKind::Text,
+            Self::UpstreamZone | Self::DownstreamZone => InputKind::Numeric,
+        }
+    }
+
+    fn next(self) -> Self {
+        Self::from_repr(self as usize + 1).unwrap_or(Self::Name)
+    }
+
+    fn previous(self) -> Self {
+        (self as usize)
+            .checked_sub(1)
+            .and_then(Self::from_repr)
+            .unwrap_or(Self::DownstreamZone)
+    }
+}
+
+/// Rows a bordered `ZoneWidget` needs to show every item.
+const ZONE_WIDGET_HEIGHT: u16 = std::mem::variant_count::<ZoneItem>() as u16 + 2;
+
+#[derive(Default)]
 pub struct ZoneWidgetState {
     selected: Option<ZoneItem>,
     selected_mode: ItemMode,
+    edit_input: Input,
+    /// What the item being edited holds, so keys it can't take are dropped.
+    input_kind: InputKind,
+    /// Where each item was last rendered, for mouse hit-testing.
+    item_areas: Vec<(ZoneItem, Rect)>,
+    /// Renders the zone as a single `name [prev→next]` line, for dense lists.
+    compact: bool,
+}
+
+impl ZoneWidgetState {
+    pub fn select_next(&mut self) {
+        self.selected = Some(self.selected.map_or(ZoneItem::Name, ZoneItem::next));
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = Some(self.selected.map_or(ZoneItem::Name, ZoneItem::previous));
+    }
+
+    /// Switches between the bordered item layout and the single compact line.
+    pub fn set_compact(&mut self, compact: bool) {
+        self.compact = compact;
+    }
+
+    fn editing(&self) -> Option<ZoneItem> {
+        match self.selected_mode {
+            ItemMode::Insert => self.selected,
+            _ => None,
+        }
+    }
 }
 
-pub struct ZoneWidget {
-    zone: Zone,
-    state: ZoneWidgetState,
+#[derive(Clone, Copy)]
+pub struct ZoneWidget<'a> {
+    zone: &'a Zone,
+    /// All zones, so links can be shown by name.
+    zones: &'a [Zone],
+    border_set: symbols::border::Set,
+    highlight_style: Style,
 }
 
-impl Default for ZoneWidget {
-    fn default() -> Self {
+impl<'a> ZoneWidget<'a> {
+    pub fn new(zone: &'a Zone, zones: &'a [Zone]) -> Self {
         Self {
-            zone: Zone::default(),
-            state: ZoneWidgetState {
-                selected: None,
-                selected_mode: ItemMode::Normal,
-            },
+            zone,
+            zones,
+            border_set: symbols::border::PLAIN,
+            highlight_style: Style::new().reversed(),
         }
     }
-}
 
-impl ZoneWidget {
+    pub fn border_set(mut self, border_set: symbols::border::Set) -> Self {
+        self.border_set = border_set;
+        self
+    }
+
+    /// Style of the selected item, reversed by default.
+    pub fn highlight_style(mut self, highlight_style: Style) -> Self {
+        self.highlight_style = highlight_style;
+        self
+    }
+
+    /// The displayed value of `item`, with links resolved to the linked zone's name.
     pub fn value(&self, item: ZoneItem) -> String {
         match item {
-            ZoneItem::Name => self.zone.name.clone(),
-            ZoneItem::UpstreamZone => format!("{}", self.zone.prev_zone),
-            ZoneItem::DownstreamZone => format!("{}", self.zone.next_zone),
+            ZoneItem::Name => self.zone.value(item),
+            ZoneItem::UpstreamZone => self.link_name(self.zone.prev_zone),
+            ZoneItem::DownstreamZone => self.link_name(self.zone.next_zone),
+        }
+    }
+
+    /// The item drawn at `position` when the widget fills `area`, or `None` on the
+    /// border or below the last item.
+    pub fn item_at(&self, area: Rect, position: Position) -> Option<ZoneItem> {
+        self.item_areas(area)
+            .into_iter()
+            .find(|(_, item_area)| item_area.contains(position))
+            .map(|(item, _)| item)
+    }
+
+    /// Where each item goes inside the border when the widget fills `area`.
+    fn item_areas(&self, area: Rect) -> Vec<(ZoneItem, Rect)> {
+        let inner_area = self.block().inner(area);
+        // One row per item, so none of them is dropped by the zip below
+        let inner_layout =
+            Layout::vertical([Constraint::Max(2); std::mem::variant_count::<ZoneItem>()])
+                .split(inner_area);
+        ZoneItem::iter().zip(inner_layout.iter().copied()).collect()
+    }
+
+    fn block(&self) -> Block<'static> {
+        Block::bordered()
+            .border_set(self.border_set)
+            .title(self.zone.name.clone())
+    }
+
+    fn link_name(&self, link: i32) -> String {
+        match usize::try_from(link).ok().map(|i| self.zones.get(i)) {
+            None => "<none>".to_owned(),
+            Some(Some(zone)) => zone.name.clone(),
+            Some(None) => format!("<missing {link}>"),
         }
     }
 }
 
-impl StatefulWidgetRef for ZoneWidget {
+impl StatefulWidgetRef for ZoneWidget<'_> {
     type State = ZoneWidgetState;
 
     fn render_ref(&self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
-        let outer_block = Block::bordered().title(self.zone.name.clone());
-        let inner_area = outer_block.inner(area);
-        let inner_layout =
-            Layout::vertical([Constraint::Max(2); std::mem::variant_count::<ZoneItem>() - 1])
-                .split(inner_area);
+        if state.compact {
+            state.item_areas.clear();
+            let line = format!(
+                "{} [{}→{}]",
+                self.zone.name,
+                self.value(ZoneItem::UpstreamZone),
+                self.value(ZoneItem::DownstreamZone)
+            );
+            Line::from(line).render_ref(area, buf);
+            if !self.zone.enabled {
+                buf.set_style(area, Modifier::DIM);
+            }
+            return;
+        }
+        self.block().render_ref(area, buf);
+        state.item_areas = self.item_areas(area);
+        for &(item, item_area) in &state.item_areas {
+            let label = format!("{}: ", item);
+            let selected = state.selected == Some(item);
+            let line = match state.selected_mode {
+                ItemMode::Insert if selected => {
+                    Line::from(format!("{}{}", label, state.edit_input.value()))
+                }
+                ItemMode::Selected(start, end) if selected => {
+                    let (before, range, after) = split_range(&self.value(item), start, end);
+                    Line::from(vec![
+                        label.clone().into(),
+                        before.into(),
+                        range.underlined(),
+                        after.into(),
+                    ])
+                    .patch_style(self.highlight_style)
+                }
+                _ if selected => Line::from(format!("{}{}", label, self.value(item)))
+                    .patch_style(self.highlight_style),
+                _ => Line::from(format!("{}{}", label, self.value(item))),
+            };
+            Paragraph::new(line).render_ref(item_area, buf);
 
-        outer_block.render_ref(area, buf);
-        for (i, item) in ZoneItem::iter().enumerate() {
-            Paragraph::new(format!("{}: {}", item.to_string(), self.value(item)))
-                .render_ref(inner_layout[i], buf);
+            // Draw a block cursor over the cell being edited, counting columns rather
+            // than chars as wide characters take two cells
+            if let ItemMode::Insert = state.selected_mode
+                && selected
+            {
+                let x = item_area.x + (label.len() + state.edit_input.visual_cursor()) as u16;
+                if x < item_area.right() {
+                    buf[(x, item_area.y)].set_style(Style::new().reversed());
+                }
+            }
+        }
+        if !self.zone.enabled {
+            buf.set_style(area, Modifier::DIM);
         }
     }
 }
 
-#[derive(Default, Clone, Copy, Display, FromRepr, EnumIter)]
-enum MenuItem {
+/// Indices of the zones along each chain of `next_zone` links, one chain per head.
+///
+/// A head is a zone without a valid `prev_zone`. Zones that are only reachable around a
+/// loop get a chain of their own, and every chain stops before a zone that is already in
+/// one, so each zone is listed once even where chains merge.
+pub fn zone_chains(zones: &[Zone]) -> Vec<Vec<usize>> {
+    let link = |link: i32| usize::try_from(link).ok().filter(|&i| i < zones.len());
+    let walk = |start: usize, seen: &mut [bool]| {
+        let mut chain = Vec::new();
+        let mut current = Some(start);
+        while let Some(i) = current.filter(|&i| !seen[i]) {
+            seen[i] = true;
+            chain.push(i);
+            current = link(zones[i].next_zone);
+        }
+        chain
+    };
+    let mut seen = vec![false; zones.len()];
+    let mut chains = Vec::new();
+    for head in (0..zones.len()).filter(|&i| link(zones[i].prev_zone).is_none()) {
+        let chain = walk(head, &mut seen);
+        if !chain.is_empty() {
+            chains.push(chain);
+        }
+    }
+    while let Some(start) = seen.iter().position(|seen| !seen) {
+        chains.push(walk(start, &mut seen));
+    }
+    chains
+}
+
+/// How well `query` matches `name` as a subsequence ignoring case, higher being
+/// better, or `None` if it doesn't match.
+///
+/// Every matched character scores, with a bonus for following the previous match and
+/// a bigger one for matching the start of `name`, so prefixes beat scattered matches.
+pub fn fuzzy_score(query: &str, name: &str) -> Option<i32> {
+    let mut name = name.chars().flat_map(char::to_lowercase).enumerate();
+    let mut last = None;
+    let mut score = 0;
+    for q in query.chars().flat_map(char::to_lowercase) {
+        let (i, _) = name.by_ref().find(|&(_, c)| c == q)?;
+        score += match (i, last) {
+            (0, _) => 4,
+            (i, Some(last)) if i == last + 1 => 3,
+            _ => 1,
+        };
+        last = Some(i);
+    }
+    Some(score)
+}
+
+/// Names of the `zones` that fuzzily match `query`, best match first.
+pub fn zone_completions<'a>(query: &str, zones: &'a [Zone]) -> Vec<&'a str> {
+    let mut matches: Vec<(i32, &str)> = zones
+        .iter()
+        .filter_map(|zone| Some((fuzzy_score(query, &zone.name)?, zone.name.as_str())))
+        .collect();
+    // Shorter names first among equals, as less of them is left unmatched
+    matches.sort_by_key(|&(score, name)| (-score, name.len(), name));
+    matches.dedup_by_key(|&mut (_, name)| name);
+    matches.into_iter().map(|(_, name)| name).collect()
+}
+
+/// Draws the zones as `A → B → C`, one chain a line, highlighting the selected zone.
+pub struct ZoneChainWidget<'a> {
+    zones: &'a [Zone],
+    selected: usize,
+}
+
+impl<'a> ZoneChainWidget<'a> {
+    pub fn new(zones: &'a [Zone], selected: usize) -> Self {
+        Self { zones, selected }
+    }
+}
+
+impl Widget for ZoneChainWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let rows = area.rows();
+        for (chain, row) in zone_chains(self.zones).into_iter().zip(rows) {
+            let mut spans = Vec::new();
+            for (n, &i) in chain.iter().enumerate() {
+                if n > 0 {
+                    spans.push(" → ".dim());
+                }
+                let zone = &self.zones[i];
+                let mut span = Span::raw(zone.name.as_str());
+                if i == self.selected {
+                    span = span.reversed();
+                }
+                if !zone.enabled {
+                    span = span.dim();
+                }
+                spans.push(span);
+            }
+            // Mark chains that were cut short by a loop
+            let last = &self.zones[chain[chain.len() - 1]];
+            if usize::try_from(last.next_zone).is_ok_and(|next| chain.contains(&next)) {
+                spans.push(" ↺".dim());
+            }
+            Line::from(spans).render(row, buf);
+        }
+    }
+}
+
+/// Splits `value` into the text before, inside and after the char range `start..end`.
+fn split_range(value: &str, start: i32, end: i32) -> (String, String, String) {
+    let len = value.chars().count();
+    let start = (start.max(0) as usize).min(len);
+    let end = (end.max(0) as usize).clamp(start, len);
+    (
+        value.chars().take(start).collect(),
+        value.chars().skip(start).take(end - start).collect(),
+        value.chars().skip(end).collect(),
+    )
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Serialize, Display, FromRepr, EnumIter)]
+pub enum MenuItem {
     #[default]
     #[strum(to_string = "Zones")]
     Zones,
@@ -138,75 +511,537 @@ impl MenuItem {
         Self::from_repr(next).unwrap_or(self)
     }
 
-    const fn palette(self) -> tailwind::Palette {
-        match self {
-            Self::Zones => tailwind::BLUE,
-            Self::Sensors => tailwind::RED,
-            Self::Motors => tailwind::GREEN,
-            Self::IO => tailwind::AMBER,
-            Self::Misc => tailwind::AMBER,
-        }
+    fn palette(self, theme: &Theme) -> tailwind::Palette {
+        theme
+            .tabs
+            .get(self as usize)
+            .and_then(|name| parse_palette(name))
+            .unwrap_or(tailwind::SLATE)
     }
 
-    fn block(self) -> Block<'static> {
+    fn block(self, theme: &Theme, border_set: symbols::border::Set) -> Block<'static> {
         Block::bordered()
-            .border_set(symbols::border::PROPORTIONAL_TALL)
+            .border_set(border_set)
             .padding(Padding::horizontal(1))
-            .border_style(self.palette().c700)
+            .border_style(self.palette(theme).c700)
+    }
+
+    fn tab_block(self, theme: &Theme, border_set: symbols::border::Set) -> Block<'static> {
+        self.block(theme, border_set).title(self.title(theme))
     }
 
-    fn title(self) -> Line<'static> {
+    fn title(self, theme: &Theme) -> Line<'static> {
         format!(" {self} ")
             .fg(tailwind::SLATE.c200)
-            .bg(self.palette().c900)
+            .bg(self.palette(theme).c900)
             .into()
     }
 
-    fn render_flap1(self, area: Rect, buf: &mut Buffer) {
-        Paragraph::new("Tab1").block(self.block()).render(area, buf);
+    /// Every tab's title in order, with `self` highlighted as the active one.
+    fn tab_bar(self, theme: &Theme) -> Tabs<'static> {
+        Tabs::new(Self::iter().map(|item| item.title(theme)))
+            .select(self as usize)
+            .highlight_style(Style::new().bg(self.palette(theme).c600).bold())
+            .padding("", "")
+            .divider(" ")
     }
 
-    fn render_flap2(self, area: Rect, buf: &mut Buffer) {
-        Paragraph::new("Tab2").block(self.block()).render(area, buf);
+    /// The tab whose title covers column `x` of a `tab_bar` drawn from column `left`.
+    fn at_column(left: u16, x: u16) -> Option<Self> {
+        let mut start = left;
+        for item in Self::iter() {
+            let end = start + format!(" {item} ").len() as u16;
+            if (start..end).contains(&x) {
+                return Some(item);
+            }
+            // One column for the divider
+            start = end + 1;
+        }
+        None
     }
+}
 
-    fn render_flap3(self, area: Rect, buf: &mut Buffer) {
-        Paragraph::new("Tab3").block(self.block()).render(area, buf);
-    }
+/// The pane keys are meant for, whose selection is highlighted at full strength.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Display, FromRepr)]
+pub enum Focus {
+    Input,
+    #[default]
+    List,
+    Zones,
+}
 
-    fn render_flap4(self, area: Rect, buf: &mut Buffer) {
-        Paragraph::new("Tab4").block(self.block()).render(area, buf);
+impl Focus {
+    fn next(self) -> Self {
+        Self::from_repr(self as usize + 1).unwrap_or(Self::Input)
     }
 }
 
-#[derive(Default, Copy, Clone, PartialEq, Eq)]
+/// Which way the counter last moved.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CounterChange {
+    Up,
+    Down,
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Display)]
 pub enum Mode {
     #[default]
     Normal,
     Insert,
+    Filter,
     Processing,
 }
 
+impl Mode {
+    /// Style of the mode badge in the footer.
+    fn style(self, theme: &Theme) -> Style {
+        let color = match self {
+            Mode::Normal => theme.modes.normal,
+            Mode::Insert => theme.modes.insert,
+            Mode::Filter => theme.modes.filter,
+            Mode::Processing => theme.modes.processing,
+        };
+        Style::default().fg(Color::Black).bg(color).bold()
+    }
+}
+
+/// Wraps `value` into `0..=max`.
+fn wrap_counter(value: i128, max: usize) -> usize {
+    value.rem_euclid(max as i128 + 1) as usize
+}
+
+/// Maps a command typed after `:` in the input box to its action.
+fn parse_command(command: &str) -> std::result::Result<Action, String> {
+    let words: Vec<&str> = command.split_whitespace().collect();
+    match words[..] {
+        ["quit"] | ["q"] => Ok(Action::Quit),
+        ["help"] | ["h"] => Ok(Action::Help),
+        ["zone", "add"] => Ok(Action::AddZone),
+        ["zone", "remove"] => Ok(Action::RemoveSelectedZone),
+        ["goto", ref name @ ..] if !name.is_empty() => Ok(Action::GotoZone(name.join(" "))),
+        ["record", path] => Ok(Action::StartRecording(path.into())),
+        ["stop"] => Ok(Action::StopRecording),
+        ["replay", path] => Ok(Action::Replay(path.into())),
+        ["counter", value] => value
+            .parse()
+            .map(Action::SetCounter)
+            .map_err(|_| format!("Not a counter value: {value}")),
+        _ => Err(format!("Unknown command: :{}", command.trim())),
+    }
+}
+
+/// Whether `action`, or the `:` command it submits, starts, stops or replays a
+/// recording, which a replay mustn't do again.
+fn controls_recording(action: &Action) -> bool {
+    match action {
+        Action::StartRecording(_) | Action::StopRecording | Action::Replay(_) => true,
+        Action::CompleteInput(s) => s
+            .strip_prefix(':')
+            .and_then(|command| parse_command(command).ok())
+            .is_some_and(|action| controls_recording(&action)),
+        _ => false,
+    }
+}
+
+/// The current UTC time of day as `HH:MM:SS`.
+fn timestamp() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        % 86_400;
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// Where the terminal cursor goes for `input` drawn `scroll` columns in, inside the
+/// bordered box at `area`.
+///
+/// Wide characters like CJK take two columns, so this counts display width rather than
+/// chars.
+fn input_cursor(input: &Input, area: Rect, scroll: usize) -> Position {
+    let column = input.visual_cursor().saturating_sub(scroll) as u16;
+    Position {
+        x: (area.x + 1 + column).min(area.right().saturating_sub(2)),
+        y: area.y + 1,
+    }
+}
+
+/// Whether `action` makes a snapshot that `Action::Undo` goes back to.
+fn is_undoable(action: &Action) -> bool {
+    matches!(
+        action,
+        Action::IncrementSingle
+            | Action::DecrementSingle
+            | Action::Increment(_)
+            | Action::Decrement(_)
+            | Action::SetCounter(_)
+            | Action::CompleteInput(_)
+            | Action::MoveEntryUp
+            | Action::MoveEntryDown
+            | Action::DeleteSelected
+            | Action::AddZone
+            | Action::InsertZoneAfter(_)
+            | Action::RemoveZone(_)
+            | Action::ToggleZoneEnabled(_)
+            | Action::ToggleSelectedZoneEnabled
+            | Action::RemoveSelectedZone
+            | Action::Load
+            | Action::ClearHistory
+            | Action::InputValidated(..)
+    )
+}
+
+/// Number of history entries kept by default before the oldest are dropped.
+const DEFAULT_MAX_HISTORY: usize = 1000;
+
+/// File in the data directory that `Action::Save`/`Action::Load` use.
+const ZONES_FILE: &str = "zones.json";
+
+/// Number of app ticks a status line message stays visible by default.
+const DEFAULT_MESSAGE_TICKS: usize = 3;
+
+/// Terminal width below which the input is stacked under the content.
+const DEFAULT_NARROW_WIDTH: u16 = 60;
+
+/// Smallest terminal the full layout is drawn into.
+const MIN_WIDTH: u16 = 20;
+const MIN_HEIGHT: u16 = 8;
+
+/// How long scheduled increments and decrements wait by default.
+const DEFAULT_SCHEDULE_DELAY: Duration = Duration::from_secs(1);
+
+/// Time between app ticks by default, matching the `--tick-rate` default.
+const DEFAULT_TICK_RATE: Duration = Duration::from_secs(1);
+
+/// Number of errors kept for the error history popup.
+const MAX_ERRORS: usize = 50;
+
+/// Number of render ticks the counter flashes for after a change, by default.
+const DEFAULT_FLASH_TICKS: usize = 15;
+
+/// Number of app ticks a partly typed key chord waits for its next key by default.
+const DEFAULT_CHORD_TICKS: usize = 4;
+
+/// Most zone names shown under the input while completing `:goto`.
+const MAX_COMPLETIONS: usize = 5;
+
+/// Number of handled actions kept for `Home::recent_actions`.
+const MAX_RECENT_ACTIONS: usize = 50;
+
+/// The spinner shown next to the mode badge while processing, by default.
+const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// Number of undo steps kept before the oldest are dropped.
+const MAX_UNDO: usize = 100;
+
+/// Number of recent counter values kept for the header sparkline.
+const COUNTER_TREND_LEN: usize = 32;
+
+/// Colors `Action::CycleTextColor` steps through.
+const TEXT_COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::White,
+    Color::LightGreen,
+    Color::Yellow,
+    Color::LightMagenta,
+    Color::LightBlue,
+];
+
+/// Number of gaps between render ticks kept for the frame times overlay.
+const FRAME_TIMES_LEN: usize = 64;
+
+/// Share of the body, in percent, that the input and history pane starts with.
+const DEFAULT_INPUT_PANE: u16 = 40;
+
+/// Smallest and largest share of the body the input pane can be resized to.
+const INPUT_PANE_RANGE: std::ops::RangeInclusive<u16> = 20..=80;
+
+/// How much `Action::GrowInput` and `Action::ShrinkInput` move the divider, in percent.
+const INPUT_PANE_STEP: u16 = 5;
+
+/// How long checking an input takes, standing in for a round trip to a server.
+const VALIDATION_DELAY: Duration = Duration::from_millis(300);
+
+/// Width of the counter gauge or sparkline at the right of the header.
+const COUNTER_WIDTH: u16 = 20;
+
+/// Start of the name of the file in the data directory that `Action::DumpState`
+/// writes to, which ends with the process id so running instances don't clash.
+const STATE_DUMP_PREFIX: &str = "state-dump-";
+
+/// File in the data directory that the counter is kept in between runs.
+pub const PERSIST_FILE: &str = "state.json";
+
+/// Format of the persisted state, bumped whenever a field changes meaning.
+const PERSIST_VERSION: u32 = 1;
+
+/// What `Home` keeps between runs.
+///
+/// Missing fields fall back to their defaults and unknown ones are skipped, so files
+/// written by other versions still load.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Persisted {
+    version: u32,
+    counter: usize,
+    input_pane: Option<u16>,
+}
+
+/// The parts of `Home` worth attaching to a bug report.
+#[derive(Serialize)]
+struct StateDump<'a> {
+    counter: usize,
+    mode: Mode,
+    zones: &'a [Zone],
+    history: &'a [String],
+    filter: &'a str,
+    selected_tab: MenuItem,
+    selected_zone: usize,
+    selected_zone_item: Option<ZoneItem>,
+    selected_history: Option<usize>,
+}
+
+/// The border symbols of each region of the screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BorderConfig {
+    pub main: symbols::border::Set,
+    pub tabs: symbols::border::Set,
+    pub zone: symbols::border::Set,
+    /// The input and filter boxes.
+    pub input: symbols::border::Set,
+    pub list: symbols::border::Set,
+}
+
+impl Default for BorderConfig {
+    fn default() -> Self {
+        Self {
+            main: symbols::border::ROUNDED,
+            tabs: symbols::border::PROPORTIONAL_TALL,
+            zone: symbols::border::PLAIN,
+            input: symbols::border::PLAIN,
+            list: symbols::border::PLAIN,
+        }
+    }
+}
+
+impl BorderConfig {
+    /// Uses `border_type` for every region.
+    pub fn all(border_type: BorderType) -> Self {
+        let set = border_type.to_border_set();
+        Self {
+            main: set,
+            tabs: set,
+            zone: set,
+            input: set,
+            list: set,
+        }
+    }
+
+    pub fn main(mut self, main: symbols::border::Set) -> Self {
+        self.main = main;
+        self
+    }
+
+    pub fn tabs(mut self, tabs: symbols::border::Set) -> Self {
+        self.tabs = tabs;
+        self
+    }
+
+    pub fn zone(mut self, zone: symbols::border::Set) -> Self {
+        self.zone = zone;
+        self
+    }
+
+    pub fn input(mut self, input: symbols::border::Set) -> Self {
+        self.input = input;
+        self
+    }
+
+    pub fn list(mut self, list: symbols::border::Set) -> Self {
+        self.list = list;
+        self
+    }
+}
+
+/// One line of an action recording, timed from the start of the recording.
+#[derive(Serialize, Deserialize)]
+struct RecordedAction {
+    at: Duration,
+    action: Action,
+}
+
+/// A delayed action that `Home::schedule` has in flight.
+#[derive(Debug)]
+pub struct ScheduledTask {
+    pub id: usize,
+    pub action: Action,
+    pub due: tokio::time::Instant,
+    handle: AbortHandle,
+}
+
+/// An action recording in progress.
+struct Recording {
+    file: BufWriter<File>,
+    started: tokio::time::Instant,
+}
+
+/// The state that undo and redo step between.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    counter: usize,
+    zones: Vec<Zone>,
+    history: Vec<String>,
+    stamps: Vec<Option<String>>,
+}
+
 #[derive(Default)]
 pub struct Home {
     pub show_help: bool,
+    pub confirm_quit: bool,
+    pub show_quit_dialog: bool,
+    pub confirm_clear_history: bool,
+    pub show_clear_dialog: bool,
     pub counter: usize,
+    pub counter_max: Option<usize>,
+    /// How far `IncrementSingle` and `DecrementSingle` move the counter.
+    pub step: usize,
+    /// Recent counter values, oldest first, for when there is no `counter_max`.
+    pub counter_trend: VecDeque<u64>,
+    pub last_change: Option<CounterChange>,
+    /// Render ticks left before the counter fades back from its flash color.
+    pub flash_remaining: usize,
+    pub flash_ticks: usize,
+    pub pending_count: Option<usize>,
+    /// Delayed actions that haven't arrived yet, oldest first.
+    pub scheduled: Vec<ScheduledTask>,
+    next_task_id: usize,
+    pub schedule_delay: Duration,
+    /// Checks each input in the background before it's added, when set.
+    pub validator: Option<fn(&str) -> bool>,
     pub app_ticker: usize,
+    /// Time between app ticks, as configured rather than measured.
+    pub tick_rate: Duration,
+    /// When the tick rate measurement started and the ticks seen since.
+    pub ticks_since: Option<(Instant, usize)>,
     pub render_ticker: usize,
     pub mode: Mode,
+    pub prev_mode: Mode,
     pub input: Input,
     pub action_tx: Option<UnboundedSender<Action>>,
     pub keymap: HashMap<KeyEvent, Action>,
+    /// Bindings of more than one key, like `gg`.
+    pub chords: HashMap<Vec<KeyEvent>, Action>,
+    /// Keys typed so far towards a chord.
+    pub pending_keys: Vec<KeyEvent>,
+    pub chord_expires: usize,
+    pub chord_ticks: usize,
     pub text: Vec<String>,
+    /// Index into `text` of the submission recalled into the input with Up, if any.
+    pub recall: Option<usize>,
     pub last_events: Vec<KeyEvent>,
     pub text_list: Vec<String>,
+    /// When each history entry was added, kept apart so filtering only sees the content.
+    pub stamps: Vec<Option<String>>,
+    /// Whether `add` stamps new entries with the time, shown before them.
+    pub timestamp_entries: bool,
     pub text_list_state: ListState,
-    pub selected_tab: ZoneWidget,
+    pub focus: Focus,
+    /// The focus to go back to once done typing.
+    pub prev_focus: Focus,
+    pub list_direction: ListDirection,
+    pub highlight_symbol: String,
+    /// Frames of the Processing spinner, advanced once per render tick.
+    pub spinner_frames: Vec<String>,
+    pub borders: BorderConfig,
+    /// Color of the selected history entry, the theme's highlight when unset.
+    pub highlight_color: Option<Color>,
+    /// Color of the main panel text, the theme's text when unset.
+    pub text_color: Option<Color>,
+    pub max_history: usize,
+    /// Whether a blank input is added to the history rather than rejected.
+    pub allow_empty_input: bool,
+    /// Whether Esc submits the input box like Enter, rather than clearing it.
+    pub commit_on_escape: bool,
+    /// Whether long lines in the main panel wrap instead of being cut off.
+    pub wrap: bool,
+    /// How many lines the main panel is scrolled down.
+    pub scroll: u16,
+    /// The furthest the main panel could scroll when it was last drawn.
+    pub max_scroll: u16,
+    /// Whether edits are ignored, so the app can be shown without accidents.
+    ///
+    /// Nothing is written to disk either, neither by `Action::Save` nor on quit.
+    pub read_only: bool,
+    pub filter: String,
+    pub filter_input: Input,
+    pub zones: Vec<Zone>,
+    pub selected_zone: usize,
+    pub selected_menu: MenuItem,
+    pub zone_widget_state: ZoneWidgetState,
+    pub sensors: Vec<Sensor>,
+    pub sensor_state: SensorWidgetState,
+    pub motors: Vec<Motor>,
+    pub motor_state: MotorWidgetState,
+    pub io_points: Vec<IoPoint>,
+    pub io_state: IoWidgetState,
+    pub misc_state: MiscWidgetState,
+    /// The status line message and the app tick it expires on.
+    pub message: Option<(Severity, String, usize)>,
+    pub message_ticks: usize,
+    pub errors: Vec<String>,
+    /// Where the history list was last rendered, for mouse hit-testing.
+    pub list_area: Rect,
+    /// Where the tab bar was last rendered, for mouse hit-testing.
+    pub tab_bar_area: Rect,
+    pub themes: Vec<Theme>,
+    pub theme: usize,
+    /// The mode to go back to once resumed, while the app is suspended.
+    pub suspended: Option<Mode>,
+    pub show_errors: bool,
+    /// How often each action went through `update`, by its name.
+    pub action_counts: HashMap<String, usize>,
+    pub show_action_counts: bool,
+    /// The last actions that went through `update`, oldest first.
+    recent: Vec<Action>,
+    pub show_recent_actions: bool,
+    /// Time between the last render ticks, oldest first.
+    pub frame_times: VecDeque<Duration>,
+    last_render_tick: Option<Instant>,
+    pub show_frame_times: bool,
+    recording: Option<Recording>,
+    /// Set while an input event is handled, so the actions it sends get recorded.
+    handling_input: bool,
+    /// Where the counter is saved on quit, once `load_persisted` has been called.
+    persist_path: Option<PathBuf>,
+    pub needs_render: bool,
+    pub size: Option<Size>,
+    pub narrow_width: u16,
+    /// Share of the body, in percent, taken by the input and history pane.
+    pub input_pane: u16,
+    pub undo_stack: Vec<Snapshot>,
+    pub redo_stack: Vec<Snapshot>,
 }
 
 impl Home {
     pub fn new() -> Self {
-        Self::default()
+        Self {
+            zones: vec![Zone::default()],
+            step: 1,
+            max_history: DEFAULT_MAX_HISTORY,
+            list_direction: ListDirection::BottomToTop,
+            highlight_symbol: ">>".to_owned(),
+            spinner_frames: SPINNER_FRAMES.map(String::from).to_vec(),
+            needs_render: true,
+            narrow_width: DEFAULT_NARROW_WIDTH,
+            input_pane: DEFAULT_INPUT_PANE,
+            message_ticks: DEFAULT_MESSAGE_TICKS,
+            chord_ticks: DEFAULT_CHORD_TICKS,
+            flash_ticks: DEFAULT_FLASH_TICKS,
+            schedule_delay: DEFAULT_SCHEDULE_DELAY,
+            tick_rate: DEFAULT_TICK_RATE,
+            themes: vec![Theme::default()],
+            ..Self::default()
+        }
     }
 
     pub fn keymap(mut self, keymap: HashMap<KeyEvent, Action>) -> Self {
@@ -214,304 +1049,5110 @@ impl Home {
         self
     }
 
-    pub fn tick(&mut self) {
-        log::info!("Tick");
-        self.app_ticker = self.app_ticker.saturating_add(1);
-        self.last_events.drain(..);
+    /// Starts with `zones` instead of a single default zone.
+    ///
+    /// Fails if a zone links to one that isn't there.
+    pub fn with_zones(mut self, zones: Vec<Zone>) -> Result<Self> {
+        validate_links(&zones)?;
+        self.zones = zones;
+        self.selected_zone = 0;
+        Ok(self)
     }
 
-    pub fn render_tick(&mut self) {
-        log::debug!("Render Tick");
-        self.render_ticker = self.render_ticker.saturating_add(1);
+    /// Starts with `history` already entered, oldest first.
+    pub fn with_history(mut self, history: Vec<String>) -> Self {
+        for entry in history {
+            self.add(entry);
+        }
+        self
     }
 
-    pub fn add(&mut self, s: String) {
-        self.text.push(s.clone());
-        self.text_list.push(s.clone());
+    pub fn chords(mut self, chords: HashMap<Vec<KeyEvent>, Action>) -> Self {
+        self.chords = chords;
+        self
     }
 
-    pub fn schedule_increment(&mut self, i: usize) {
-        let tx = self.action_tx.clone().unwrap();
-        tokio::spawn(async move {
-            tx.send(Action::EnterProcessing).unwrap();
-            tokio::time::sleep(Duration::from_secs(1)).await;
-            tx.send(Action::Increment(i)).unwrap();
-            tx.send(Action::ExitProcessing).unwrap();
-        });
+    /// Sets how many app ticks a partly typed chord waits before it is dropped.
+    pub fn chord_ticks(mut self, chord_ticks: usize) -> Self {
+        self.chord_ticks = chord_ticks;
+        self
     }
 
-    pub fn schedule_decrement(&mut self, i: usize) {
-        let tx = self.action_tx.clone().unwrap();
-        tokio::spawn(async move {
-            tx.send(Action::EnterProcessing).unwrap();
-            tokio::time::sleep(Duration::from_secs(1)).await;
-            tx.send(Action::Decrement(i)).unwrap();
-            tx.send(Action::ExitProcessing).unwrap();
-        });
+    /// Sets how many render ticks the counter flashes for after it changes.
+    pub fn flash_ticks(mut self, flash_ticks: usize) -> Self {
+        self.flash_ticks = flash_ticks;
+        self
     }
 
-    pub fn increment(&mut self, i: usize) {
-        self.counter = self.counter.saturating_add(i);
-        self.text_list_state.select_next();
+    /// Sets the time between app ticks that is shown next to the measured rate.
+    pub fn tick_rate(mut self, tick_rate: Duration) -> Self {
+        self.tick_rate = tick_rate;
+        self
     }
 
-    pub fn decrement(&mut self, i: usize) {
-        self.counter = self.counter.saturating_sub(i);
-        self.text_list_state.select_previous();
+    /// Sets whether the history list grows from the bottom or from the top.
+    pub fn list_direction(mut self, list_direction: ListDirection) -> Self {
+        self.list_direction = list_direction;
+        self
     }
 
-    pub fn next_tab(&mut self) {
-        self.selected_tab = self.selected_tab.next();
+    pub fn borders(mut self, borders: BorderConfig) -> Self {
+        self.borders = borders;
+        self
     }
 
-    pub fn previous_tab(&mut self) {
-        self.selected_tab = self.selected_tab.previous();
+    pub fn spinner_frames(mut self, frames: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.spinner_frames = frames.into_iter().map(Into::into).collect();
+        self
     }
-}
 
-impl Component for Home {
-    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
-        self.action_tx = Some(tx);
-        Ok(())
+    pub fn highlight_symbol(mut self, highlight_symbol: impl Into<String>) -> Self {
+        self.highlight_symbol = highlight_symbol.into();
+        self
     }
 
-    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
-        self.last_events.push(key.clone());
-        let action = match self.mode {
-            Mode::Normal | Mode::Processing => return Ok(None),
-            Mode::Insert => match key.code {
-                KeyCode::Esc => Action::EnterNormal,
-                KeyCode::Enter => {
-                    if let Some(sender) = &self.action_tx {
-                        if let Err(e) =
-                            sender.send(Action::CompleteInput(self.input.value().to_string()))
-                        {
-                            error!("Failed to send action: {:?}", e);
-                        }
-                    }
-                    Action::EnterNormal
+    /// Colors the selected history entry instead of using the theme's highlight.
+    pub fn highlight_color(mut self, highlight_color: Color) -> Self {
+        self.highlight_color = Some(highlight_color);
+        self
+    }
+
+    pub fn text_color(mut self, text_color: Color) -> Self {
+        self.text_color = Some(text_color);
+        self
+    }
+
+    pub fn allow_empty_input(mut self, allow_empty_input: bool) -> Self {
+        self.allow_empty_input = allow_empty_input;
+        self
+    }
+
+    pub fn commit_on_escape(mut self, commit_on_escape: bool) -> Self {
+        self.commit_on_escape = commit_on_escape;
+        self
+    }
+
+    /// Asks for confirmation before `Action::RequestQuit` quits.
+    pub fn confirm_quit(mut self, confirm_quit: bool) -> Self {
+        self.confirm_quit = confirm_quit;
+        self
+    }
+
+    /// Asks for confirmation before `Action::RequestClearHistory` clears the history.
+    pub fn confirm_clear_history(mut self, confirm_clear_history: bool) -> Self {
+        self.confirm_clear_history = confirm_clear_history;
+        self
+    }
+
+    pub fn step(mut self, step: usize) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// Makes the counter wrap around within `0..=max` instead of saturating.
+    pub fn counter_max(mut self, max: usize) -> Self {
+        self.counter_max = Some(max);
+        self
+    }
+
+    /// Caps the number of history entries kept; `0` keeps everything.
+    pub fn max_history(mut self, max_history: usize) -> Self {
+        self.max_history = max_history;
+        self
+    }
+
+    pub fn timestamp_entries(mut self, timestamp_entries: bool) -> Self {
+        self.timestamp_entries = timestamp_entries;
+        self
+    }
+
+    /// Sets how long scheduled increments and decrements wait before applying.
+    pub fn schedule_delay(mut self, schedule_delay: Duration) -> Self {
+        self.schedule_delay = schedule_delay;
+        self
+    }
+
+    /// Sets how many app ticks a status message stays visible.
+    pub fn message_ticks(mut self, message_ticks: usize) -> Self {
+        self.message_ticks = message_ticks;
+        self
+    }
+
+    /// Checks each input with `validator` in a background task before adding it, so
+    /// only inputs it accepts reach the history.
+    pub fn validate_input(mut self, validator: fn(&str) -> bool) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    /// Sets the terminal width below which the layout stacks vertically.
+    pub fn narrow_width(mut self, narrow_width: u16) -> Self {
+        self.narrow_width = narrow_width;
+        self
+    }
+
+    /// Moves the divider between the content and the input pane by `step` percent
+    /// of the body, growing the input pane for a positive `step`.
+    pub fn resize_input_pane(&mut self, step: i16) {
+        self.input_pane = self
+            .input_pane
+            .saturating_add_signed(step)
+            .clamp(*INPUT_PANE_RANGE.start(), *INPUT_PANE_RANGE.end());
+    }
+
+    /// How the body is split between the content and the input pane.
+    pub fn body_constraints(&self) -> [Constraint; 2] {
+        [
+            Constraint::Percentage(100 - self.input_pane),
+            Constraint::Percentage(self.input_pane),
+        ]
+    }
+
+    /// Whether the input goes under the content rather than beside it.
+    ///
+    /// Uses the size from the last `Action::Resize`, or `area` before one arrives.
+    pub fn stacked_layout(&self, area: Rect) -> bool {
+        self.size.map_or(area.width, |size| size.width) < self.narrow_width
+    }
+
+    /// The action `key` is bound to in the keymap.
+    ///
+    /// Terminals differ in whether shifted symbols like `?` carry `SHIFT`, so a
+    /// binding without it matches either way.
+    pub fn bound_action(&self, key: KeyEvent) -> Option<&Action> {
+        self.keymap.get(&key).or_else(|| match key.code {
+            KeyCode::Char(_) if key.modifiers.contains(KeyModifiers::SHIFT) => self.keymap.get(
+                &KeyEvent::new(key.code, key.modifiers - KeyModifiers::SHIFT),
+            ),
+            _ => None,
+        })
+    }
+
+    /// Feeds `key` to the chord being typed.
+    ///
+    /// Returns the chord's action once it is complete, or `Action::Update` while
+    /// it is still a prefix of some chord. Keys that don't continue a chord drop
+    /// it and are left to the single key bindings.
+    fn chord_action(&mut self, key: KeyEvent) -> Option<Action> {
+        let mut keys = std::mem::take(&mut self.pending_keys);
+        keys.push(key);
+        if let Some(action) = self.chords.get(&keys) {
+            return Some(action.clone());
+        }
+        if self.chords.keys().any(|chord| chord.starts_with(&keys)) {
+            self.pending_keys = keys;
+            self.chord_expires = self.app_ticker + self.chord_ticks;
+            return Some(Action::Update);
+        }
+        None
+    }
+
+    /// Rows of the help popup: every key and chord with its action, sorted by key.
+    pub fn help_rows(&self) -> Vec<[String; 2]> {
+        let mut rows: Vec<_> = self
+            .keymap
+            .iter()
+            // Debug rather than Display, so `SelectTab(1)` keeps its argument
+            .map(|(key, action)| [key_event_to_string(key), format!("{action:?}")])
+            .chain(self.chords.iter().map(|(keys, action)| {
+                let keys = keys.iter().map(key_event_to_string).collect::<Vec<_>>();
+                [keys.join(" "), format!("{action:?}")]
+            }))
+            // Counts aren't bindings, but they are why tabs are on Alt and not bare digits
+            .chain([[
+                "0-9".to_owned(),
+                "Count for the next action, tabs are Alt-1..5".to_owned(),
+            ]])
+            .collect();
+        rows.sort();
+        rows
+    }
+
+    /// The last actions handled by `update`, oldest first.
+    pub fn recent_actions(&self) -> &[Action] {
+        &self.recent
+    }
+
+    /// The tallies of handled actions, most frequent first.
+    pub fn count_rows(&self) -> Vec<[String; 2]> {
+        let mut counts: Vec<_> = self.action_counts.iter().collect();
+        counts.sort_by(|(a, x), (b, y)| y.cmp(x).then(a.cmp(b)));
+        counts
+            .into_iter()
+            .map(|(action, count)| [count.to_string(), action.clone()])
+            .collect()
+    }
+
+    /// Draws a two column table in a bordered popup, sized to fit its rows.
+    fn render_table_popup(
+        &self,
+        f: &mut Frame<'_>,
+        rect: Rect,
+        title: &str,
+        header: [&str; 2],
+        rows: Vec<[String; 2]>,
+    ) {
+        let column_width = |i: usize| {
+            let width = rows.iter().map(|row| row[i].len()).max().unwrap_or(0);
+            width.max(header[i].len()) as u16
+        };
+        let (first_width, second_width) = (column_width(0), column_width(1));
+        // The spacing between the columns, and the header row with the margin below it
+        let size = Size::new(first_width + second_width + 1, rows.len() as u16 + 2);
+
+        let widths = [Constraint::Length(first_width), Constraint::Fill(1)];
+        let table = Table::new(rows.into_iter().map(Row::new), widths)
+            .header(
+                Row::new(header)
+                    .bottom_margin(1)
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+            )
+            .column_spacing(1);
+        render_modal(
+            f,
+            rect,
+            title.bold().into(),
+            Style::default().fg(self.theme().border),
+            size,
+            table,
+        );
+    }
+
+    /// Ticks per second measured since the first tick, or since the rate last changed.
+    pub fn measured_tick_rate(&self, now: Instant) -> Option<f64> {
+        let (since, ticks) = self.ticks_since?;
+        let elapsed = now.duration_since(since).as_secs_f64();
+        // The first tick starts the clock, so it isn't counted
+        (elapsed > 0.0).then(|| (ticks - 1) as f64 / elapsed)
+    }
+
+    pub fn tick(&mut self) {
+        if self.suspended.is_some() {
+            return;
+        }
+        log::info!("Tick");
+        self.app_ticker = self.app_ticker.saturating_add(1);
+        let (_, ticks) = self.ticks_since.get_or_insert((Instant::now(), 0));
+        *ticks += 1;
+        // The Misc tab shows the tickers and tick rate as they go
+        if self.selected_menu == MenuItem::Misc {
+            self.needs_render = true;
+        }
+        if !self.last_events.is_empty() {
+            self.last_events.clear();
+            self.needs_render = true;
+        }
+        if !self.pending_keys.is_empty() && self.app_ticker >= self.chord_expires {
+            self.pending_keys.clear();
+        }
+        if self
+            .message
+            .as_ref()
+            .is_some_and(|(_, _, expires)| self.app_ticker >= *expires)
+        {
+            self.message = None;
+            self.needs_render = true;
+        }
+    }
+
+    /// Sends `action` to the app, reporting it in the UI if the channel is closed.
+    pub fn send_action(&mut self, action: Action) {
+        if self.handling_input {
+            self.record_action(&action);
+        }
+        if let Some(sender) = &self.action_tx
+            && let Err(e) = sender.send(action)
+        {
+            error!("Failed to send action: {:?}", e);
+            // The channel is gone, so the error can't go through it as an `Action::Error`
+            self.report_error(format!("Failed to send {}: channel closed", e.0));
+        }
+    }
+
+    pub fn show_message(&mut self, message: String) {
+        self.notify(Severity::Info, message);
+    }
+
+    /// Shows `error` in the status line and keeps it in the error history.
+    pub fn report_error(&mut self, error: String) {
+        self.notify(Severity::Error, error);
+    }
+
+    /// Shows `message` in the status line, keeping warnings and errors longer and in
+    /// the error history.
+    pub fn notify(&mut self, severity: Severity, message: String) {
+        let ticks = match severity {
+            Severity::Info => self.message_ticks,
+            Severity::Warn => self.message_ticks * 2,
+            Severity::Error => self.message_ticks * 3,
+        };
+        if severity >= Severity::Warn {
+            if self.errors.len() == MAX_ERRORS {
+                self.errors.remove(0);
+            }
+            self.errors.push(message.clone());
+        }
+        self.message = Some((severity, message, self.app_ticker + ticks));
+    }
+
+    pub fn render_tick(&mut self) {
+        log::debug!("Render Tick");
+        self.render_ticker = self.render_ticker.saturating_add(1);
+        if self.selected_menu == MenuItem::Misc {
+            self.needs_render = true;
+        }
+        let now = Instant::now();
+        if let Some(last) = self.last_render_tick.replace(now) {
+            if self.frame_times.len() == FRAME_TIMES_LEN {
+                self.frame_times.pop_front();
+            }
+            self.frame_times.push_back(now - last);
+            if self.show_frame_times {
+                self.needs_render = true;
+            }
+        }
+        // Keep the spinner turning
+        if self.spinner_frame().is_some() {
+            self.needs_render = true;
+        }
+        if self.flash_remaining > 0 {
+            self.flash_remaining -= 1;
+            self.needs_render = true;
+        }
+    }
+
+    pub fn add(&mut self, s: String) {
+        self.text.push(s.clone());
+        self.text_list.push(s);
+        self.stamps.push(self.timestamp_entries.then(timestamp));
+        if self.max_history > 0 && self.text_list.len() > self.max_history {
+            let excess = self.text_list.len() - self.max_history;
+            // The selection indexes the filtered view, so only visible entries shift it
+            let removed = self.text_list[..excess]
+                .iter()
+                .filter(|entry| self.matches_filter(entry))
+                .count();
+            self.text.drain(..excess);
+            self.text_list.drain(..excess);
+            self.stamps.drain(..excess.min(self.stamps.len()));
+            // Keep the selection on the same entry now that the older ones are gone
+            if let Some(selected) = self.text_list_state.selected() {
+                self.text_list_state
+                    .select(Some(selected.saturating_sub(removed)));
+            }
+            *self.text_list_state.offset_mut() =
+                self.text_list_state.offset().saturating_sub(removed);
+        }
+    }
+
+    /// The history entry at `index` as shown, after its time stamp if it has one.
+    pub fn display_entry(&self, index: usize) -> String {
+        match self.stamps.get(index).cloned().flatten() {
+            Some(stamp) => format!("{stamp} {}", self.text_list[index]),
+            None => self.text_list[index].clone(),
+        }
+    }
+
+    /// Renders `entry` with the time stamp at `index`, dimmed, in front of it.
+    fn stamped_line(&self, index: usize, entry: &str) -> Line<'static> {
+        match self.stamps.get(index).cloned().flatten() {
+            Some(stamp) => Line::from(vec![stamp.dim(), " ".into(), entry.to_string().into()]),
+            None => Line::from(entry.to_string()),
+        }
+    }
+
+    fn matches_filter(&self, entry: &str) -> bool {
+        entry.to_lowercase().contains(&self.filter.to_lowercase())
+    }
+
+    /// Indices into `text_list` of the entries shown with the current filter.
+    pub fn visible_history(&self) -> Vec<usize> {
+        (0..self.text_list.len())
+            .filter(|&i| self.matches_filter(&self.text_list[i]))
+            .collect()
+    }
+
+    /// Index into `text_list` of the highlighted entry, if any.
+    fn selected_entry(&self) -> Option<usize> {
+        let visible = self.visible_history();
+        // The list only clamps the selection when rendering, so it may be past the end
+        let selected = self
+            .text_list_state
+            .selected()?
+            .min(visible.len().checked_sub(1)?);
+        Some(visible[selected])
+    }
+
+    pub fn delete_selected(&mut self) {
+        let Some(entry) = self.selected_entry() else {
+            self.text_list_state.select(None);
+            return;
+        };
+        let selected = self.text_list_state.selected().unwrap_or_default();
+        self.text.remove(entry);
+        self.text_list.remove(entry);
+        if entry < self.stamps.len() {
+            self.stamps.remove(entry);
+        }
+        match self.visible_history().len() {
+            0 => self.text_list_state.select(None),
+            len => self.text_list_state.select(Some(selected.min(len - 1))),
+        }
+    }
+
+    /// Deletes every history entry and drops the selection.
+    pub fn clear_history(&mut self) {
+        self.text.clear();
+        self.text_list.clear();
+        self.stamps.clear();
+        self.text_list_state = ListState::default();
+    }
+
+    /// Whether up on screen is towards the start of the history, which is drawn at
+    /// the bottom when the list goes `BottomToTop`.
+    fn up_is_older(&self) -> bool {
+        self.list_direction == ListDirection::TopToBottom
+    }
+
+    /// Swaps the highlighted history entry with its visible neighbor above it on
+    /// screen, or below it if `up` is false, keeping it highlighted.
+    pub fn move_selected(&mut self, up: bool) {
+        let Some(selected) = self.text_list_state.selected() else {
+            return;
+        };
+        let target = if up == self.up_is_older() {
+            selected.checked_sub(1)
+        } else {
+            selected.checked_add(1)
+        };
+        let visible = self.visible_history();
+        let (Some(&from), Some(&to)) = (visible.get(selected), target.and_then(|t| visible.get(t)))
+        else {
+            return;
+        };
+        self.text.swap(from, to);
+        self.text_list.swap(from, to);
+        if from.max(to) < self.stamps.len() {
+            self.stamps.swap(from, to);
+        }
+        self.text_list_state.select(target);
+    }
+
+    /// Narrows the history list to entries containing `filter`, ignoring case.
+    ///
+    /// The highlighted entry stays selected if it is still visible, otherwise
+    /// the selection moves to the first visible one.
+    pub fn set_filter(&mut self, filter: String) {
+        let current = self.selected_entry();
+        self.filter = filter;
+        let visible = self.visible_history();
+        let selected =
+            current.map(|entry| visible.iter().position(|&i| i == entry).unwrap_or_default());
+        self.text_list_state =
+            ListState::default().with_selected(selected.filter(|_| !visible.is_empty()));
+    }
+
+    pub fn clear_filter(&mut self) {
+        self.set_filter(String::new());
+        self.filter_input.reset();
+        if self.mode == Mode::Filter {
+            self.set_mode(Mode::Normal);
+        }
+    }
+
+    /// Puts the highlighted history entry on the system clipboard.
+    pub fn copy_selected(&mut self) {
+        let Some(entry) = self.selected_entry() else {
+            return;
+        };
+        let entry = self.text_list[entry].as_str();
+        if let Err(e) = arboard::Clipboard::new().and_then(|mut c| c.set_text(entry)) {
+            self.send_action(Action::Error(format!("Copy failed: {e}")));
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            counter: self.counter,
+            zones: self.zones.clone(),
+            history: self.text_list.clone(),
+            stamps: self.stamps.clone(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.counter = snapshot.counter;
+        self.zones = snapshot.zones;
+        self.selected_zone = self.selected_zone.min(self.zones.len().saturating_sub(1));
+        self.text = snapshot.history.clone();
+        self.text_list = snapshot.history;
+        self.stamps = snapshot.stamps;
+        match self.visible_history().len() {
+            0 => self.text_list_state.select(None),
+            len => {
+                if let Some(selected) = self.text_list_state.selected() {
+                    self.text_list_state.select(Some(selected.min(len - 1)));
                 }
-                _ => {
-                    self.input.handle_event(&crossterm::event::Event::Key(key));
-                    Action::Update
+            }
+        }
+    }
+
+    /// Pushes `before` onto the undo stack if the state has changed since it was taken.
+    fn record(&mut self, before: Snapshot) {
+        if before == self.snapshot() {
+            return;
+        }
+        if self.undo_stack.len() == MAX_UNDO {
+            self.undo_stack.remove(0);
+        }
+        self.undo_stack.push(before);
+        self.redo_stack.clear();
+    }
+
+    pub fn undo(&mut self) {
+        if let Some(snapshot) = self.undo_stack.pop() {
+            self.redo_stack.push(self.snapshot());
+            self.restore(snapshot);
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(snapshot) = self.redo_stack.pop() {
+            self.undo_stack.push(self.snapshot());
+            self.restore(snapshot);
+        }
+    }
+
+    pub fn theme(&self) -> &Theme {
+        // `Home::default` has no themes, so fall back rather than index out of bounds
+        static FALLBACK: std::sync::LazyLock<Theme> = std::sync::LazyLock::new(Theme::default);
+        self.themes.get(self.theme).unwrap_or(&FALLBACK)
+    }
+
+    /// Style of the selected history entry, muted unless the list has focus.
+    fn list_highlight(&self) -> Style {
+        if self.focus != Focus::List {
+            return self.theme().muted_highlight;
+        }
+        self.highlight_color
+            .unwrap_or(self.theme().highlight)
+            .into()
+    }
+
+    /// Switches the main panel text to the color after the current one in `TEXT_COLORS`.
+    pub fn cycle_text_color(&mut self) {
+        let current = self.text_color.unwrap_or(self.theme().text);
+        let next = TEXT_COLORS
+            .iter()
+            .position(|&color| color == current)
+            .map_or(0, |i| (i + 1) % TEXT_COLORS.len());
+        self.text_color = Some(TEXT_COLORS[next]);
+        self.show_message(format!("Text color: {}", TEXT_COLORS[next]));
+    }
+
+    pub fn cycle_theme(&mut self) {
+        if !self.themes.is_empty() {
+            self.theme = (self.theme + 1) % self.themes.len();
+            self.show_message(format!("Theme: {}", self.theme().name));
+        }
+    }
+
+    /// Index into the filtered history of the list row at `position`, if any.
+    fn history_row_at(&self, position: Position) -> Option<usize> {
+        let inner = self.list_area.inner(Margin::new(1, 1));
+        if !inner.contains(position) {
+            return None;
+        }
+        let row = match self.list_direction {
+            ListDirection::TopToBottom => position.y - inner.y,
+            ListDirection::BottomToTop => inner.bottom() - 1 - position.y,
+        } as usize;
+        let index = self.text_list_state.offset() + row;
+        (index < self.visible_history().len()).then_some(index)
+    }
+
+    /// Serializes the user-visible state, for debugging.
+    pub fn dump_state(&self) -> serde_json::Value {
+        let dump = StateDump {
+            counter: self.counter,
+            mode: self.mode,
+            zones: &self.zones,
+            history: &self.text_list,
+            filter: &self.filter,
+            selected_tab: self.selected_menu,
+            selected_zone: self.selected_zone,
+            selected_zone_item: self.zone_widget_state.selected,
+            selected_history: self.selected_entry(),
+        };
+        serde_json::to_value(dump).unwrap_or_default()
+    }
+
+    /// Writes `dump_state` to a file of this process in the data directory.
+    fn write_state_dump(&mut self) {
+        let dir = get_data_dir();
+        let path = dir.join(format!("{STATE_DUMP_PREFIX}{}.json", std::process::id()));
+        let result = serde_json::to_string_pretty(&self.dump_state())
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                std::fs::create_dir_all(&dir)
+                    .and_then(|()| std::fs::write(&path, json))
+                    .map_err(|e| e.to_string())
+            });
+        match result {
+            Ok(()) => {
+                log::info!("Dumped state to {}", path.display());
+                self.show_message(format!("Dumped state to {}", path.display()));
+            }
+            Err(e) => self.report_error(format!("Failed to dump state: {e}")),
+        }
+    }
+
+    /// Starts writing the actions going through `update` to `path`, one JSON object a line.
+    pub fn start_recording(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        self.recording = Some(Recording {
+            file,
+            started: tokio::time::Instant::now(),
+        });
+        Ok(())
+    }
+
+    pub fn stop_recording(&mut self) -> Result<()> {
+        if let Some(mut recording) = self.recording.take() {
+            recording.file.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Writes `action` to the recording, if there is one.
+    ///
+    /// Only actions coming from input are recorded, as whatever they lead to
+    /// follows again when they are replayed.
+    fn record_action(&mut self, action: &Action) {
+        // Replaying ticks and renders would run the app at twice its rate
+        if matches!(action, Action::Tick | Action::Render | Action::Update)
+            || controls_recording(action)
+        {
+            return;
+        }
+        let Some(recording) = &mut self.recording else {
+            return;
+        };
+        let line = RecordedAction {
+            at: recording.started.elapsed(),
+            action: action.clone(),
+        };
+        let result = serde_json::to_string(&line)
+            .map_err(|e| e.to_string())
+            .and_then(|json| writeln!(recording.file, "{json}").map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            self.recording = None;
+            self.report_error(format!("Stopped recording: {e}"));
+        }
+    }
+
+    /// Sends the actions recorded at `path` to the app, at the pace they were recorded.
+    pub fn replay(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let Some(tx) = self.action_tx.clone() else {
+            return Err(eyre!("Cannot replay: no action handler registered"));
+        };
+        let recorded = std::fs::read_to_string(path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<serde_json::Result<Vec<RecordedAction>>>()?;
+        tokio::spawn(async move {
+            let started = tokio::time::Instant::now();
+            for RecordedAction { at, action } in recorded {
+                tokio::time::sleep_until(started + at).await;
+                if tx.send(action).is_err() {
+                    return;
                 }
-            },
+            }
+        });
+        Ok(())
+    }
+
+    /// Closes the help, drops the filter and status message, and rechecks the zone links.
+    pub fn refresh(&mut self) {
+        self.show_help = false;
+        self.clear_filter();
+        self.message = None;
+        if let Err(e) = validate_links(&self.zones) {
+            self.report_error(e.to_string());
+        }
+        self.needs_render = true;
+    }
+
+    /// The spinner frame for the current render tick, while processing.
+    pub fn spinner_frame(&self) -> Option<&str> {
+        if self.mode != Mode::Processing || self.spinner_frames.is_empty() {
+            return None;
+        }
+        let frame = &self.spinner_frames[self.render_ticker % self.spinner_frames.len()];
+        Some(frame)
+    }
+
+    /// Renders the mode badge, the status message and the keys pressed this tick.
+    fn render_footer(&self, f: &mut Frame<'_>, area: Rect) {
+        let mut badge = format!(" {} ", self.mode.to_string().to_uppercase());
+        if self.mode == Mode::Processing {
+            badge.push_str(&format!("{:?} ", self.schedule_delay));
+        }
+        let [badge_area, mut message_area] =
+            Layout::horizontal([Constraint::Length(badge.len() as u16), Constraint::Fill(1)])
+                .spacing(1)
+                .areas(area);
+        f.render_widget(
+            Span::styled(badge, self.mode.style(self.theme())),
+            badge_area,
+        );
+        if let Some(frame) = self.spinner_frame() {
+            let spinner = Span::raw(frame);
+            let [spinner_area, rest] = Layout::horizontal([
+                Constraint::Length(spinner.width() as u16),
+                Constraint::Fill(1),
+            ])
+            .spacing(1)
+            .areas(message_area);
+            f.render_widget(spinner, spinner_area);
+            message_area = rest;
+        }
+        f.render_widget(
+            Block::default()
+                .title(
+                    Line::from(format!(
+                        "{:?}",
+                        self.last_events
+                            .iter()
+                            .map(key_event_to_string)
+                            .collect::<Vec<_>>()
+                    ))
+                    .right_aligned(),
+                )
+                .title(match &self.message {
+                    Some((severity, message, _)) => {
+                        let color = match severity {
+                            Severity::Info => Color::Cyan,
+                            Severity::Warn => Color::Yellow,
+                            Severity::Error => Color::Red,
+                        };
+                        Line::from(message.clone()).left_aligned().fg(color)
+                    }
+                    None => Line::default(),
+                })
+                .title_style(Style::default().add_modifier(Modifier::BOLD)),
+            message_area,
+        );
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Switches to `mode`, keeping the state that depends on the mode in step.
+    pub fn set_mode(&mut self, mode: Mode) {
+        // Overlapping scheduled tasks must not clobber the mode to return to
+        if mode == Mode::Processing && self.mode != Mode::Processing {
+            self.prev_mode = self.mode;
+        }
+        // The input keeps its text, but typing resumes at the end of it
+        if self.mode == Mode::Insert && mode != Mode::Insert {
+            self.input.handle(InputRequest::GoToEnd);
+            self.focus = self.prev_focus;
+        }
+        // Keys now go to whatever is being edited
+        if mode == Mode::Insert && self.mode != Mode::Insert {
+            self.prev_focus = self.focus;
+            self.focus = match self.zone_widget_state.editing() {
+                Some(_) => Focus::Zones,
+                None => Focus::Input,
+            };
+        }
+        self.mode = mode;
+    }
+
+    /// Commits or discards whatever is being typed, before going back to Normal mode.
+    ///
+    /// A zone field is always discarded, since Enter already committed it if wanted.
+    /// The input box is submitted if `commit_on_escape` is set, and cleared otherwise.
+    fn leave_insert(&mut self) {
+        self.recall = None;
+        if self.zone_widget_state.editing().is_some() {
+            self.cancel_zone_edit();
+        } else if self.commit_on_escape && !self.input.value().trim().is_empty() {
+            self.send_action(Action::CompleteInput(self.input.value().to_string()));
+            self.input.reset();
+        } else {
+            self.input.reset();
+        }
+    }
+
+    /// Whether `action` would edit anything, and so is ignored while read-only.
+    ///
+    /// `:commands` still run, since whatever they send is checked in turn.
+    fn is_mutating(&self, action: &Action) -> bool {
+        match action {
+            Action::CompleteInput(s) => !s.starts_with(':'),
+            Action::EnterInsert => {
+                self.selected_menu == MenuItem::Zones && self.zone_widget_state.selected.is_some()
+            }
+            action => {
+                is_undoable(action)
+                    || matches!(
+                        action,
+                        Action::RequestClearHistory
+                            | Action::EditZoneItem
+                            | Action::ScheduleIncrement
+                            | Action::ScheduleDecrement
+                            | Action::Undo
+                            | Action::Redo
+                            | Action::Save
+                    )
+            }
+        }
+    }
+
+    /// Replaces the input with the submission before the recalled one, or the last
+    /// one if none is recalled yet, like a shell's history.
+    pub fn recall_previous(&mut self) {
+        let previous = match self.recall {
+            Some(index) => index.checked_sub(1),
+            None => self.text.len().checked_sub(1),
+        };
+        if let Some(entry) = previous.and_then(|index| self.text.get(index)) {
+            self.input = Input::new(entry.clone());
+            self.recall = previous;
+        }
+    }
+
+    /// Replaces the input with the submission after the recalled one, or empties it
+    /// once past the newest.
+    pub fn recall_next(&mut self) {
+        let Some(index) = self.recall else {
+            return;
+        };
+        self.recall = Some(index + 1).filter(|&next| next < self.text.len());
+        let entry = self.recall.and_then(|index| self.text.get(index));
+        self.input = Input::new(entry.cloned().unwrap_or_default());
+    }
+
+    /// Zone names that complete the `:goto` being typed into the input, best first.
+    pub fn completions(&self) -> Vec<&str> {
+        if self.mode != Mode::Insert || self.zone_widget_state.editing().is_some() {
+            return Vec::new();
+        }
+        match self.input.value().strip_prefix(":goto ") {
+            Some(query) => zone_completions(query.trim_start(), &self.zones),
+            None => Vec::new(),
+        }
+    }
+
+    /// Lists the `completions` in a dropdown under the input box at `input_area`.
+    fn render_completions(&self, f: &mut Frame<'_>, input_area: Rect) {
+        let completions = self.completions();
+        if completions.is_empty() {
+            return;
+        }
+        let rows = completions.len().min(MAX_COMPLETIONS) as u16;
+        let area = Rect {
+            y: input_area.bottom(),
+            height: rows + 2,
+            ..input_area
+        }
+        .intersection(f.area());
+        let list = List::new(completions.into_iter().take(MAX_COMPLETIONS))
+            .block(
+                Block::bordered()
+                    .border_set(self.borders.list)
+                    .title("Tab to complete"),
+            )
+            .highlight_style(self.theme().highlight);
+        f.render_widget(Clear, area);
+        f.render_stateful_widget(list, area, &mut ListState::default().with_selected(Some(0)));
+    }
+
+    /// Asks `question` in a small box centered in `area`.
+    fn render_confirm_dialog(&self, f: &mut Frame<'_>, area: Rect, question: &str) {
+        let rect = centered_area(area, question.len() as u16 + 9, 3);
+        f.render_widget(Clear, rect);
+        f.render_widget(
+            Paragraph::new(question).centered().block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(self.theme().border)),
+            ),
+            rect,
+        );
+    }
+
+    /// Whether keys are going into a text box rather than being bound actions.
+    fn is_typing(&self) -> bool {
+        matches!(self.mode, Mode::Insert | Mode::Filter)
+    }
+
+    /// Selects the oldest visible history entry.
+    pub fn goto_top(&mut self) {
+        if !self.visible_history().is_empty() {
+            self.text_list_state.select(Some(0));
+        }
+    }
+
+    /// Selects the newest visible history entry.
+    pub fn goto_bottom(&mut self) {
+        if let Some(last) = self.visible_history().len().checked_sub(1) {
+            self.text_list_state.select(Some(last));
+        }
+    }
+
+    /// Selects the visible history entry at `index`, or the newest if it is past the end.
+    pub fn goto_index(&mut self, index: usize) {
+        if let Some(last) = self.visible_history().len().checked_sub(1) {
+            self.text_list_state.select(Some(index.min(last)));
+        }
+    }
+
+    /// Moves the history selection a page of rows up on screen, or down if `up` is
+    /// false, stopping at either end.
+    ///
+    /// A page is as many rows as the list showed when it was last drawn.
+    pub fn page_list(&mut self, up: bool) {
+        let Some(last) = self.visible_history().len().checked_sub(1) else {
+            return;
+        };
+        let page = (self.list_area.height.saturating_sub(2) as usize).max(1);
+        let selected = self
+            .text_list_state
+            .selected()
+            .unwrap_or_default()
+            .min(last);
+        let target = if up == self.up_is_older() {
+            selected.saturating_sub(page)
+        } else {
+            selected.saturating_add(page).min(last)
+        };
+        self.text_list_state.select(Some(target));
+    }
+
+    /// Scrolls the history list back towards older entries.
+    pub fn scroll_history_up(&mut self) {
+        *self.text_list_state.offset_mut() = self.text_list_state.offset().saturating_sub(1);
+    }
+
+    /// Scrolls the history list forward towards newer entries.
+    pub fn scroll_history_down(&mut self) {
+        let last = self.visible_history().len().saturating_sub(1);
+        *self.text_list_state.offset_mut() = (self.text_list_state.offset() + 1).min(last);
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    /// Scrolls the main panel down, stopping once its last line is at the bottom.
+    pub fn scroll_down(&mut self) {
+        self.scroll = (self.scroll + 1).min(self.max_scroll);
+    }
+
+    pub fn schedule_increment(&mut self, i: usize) {
+        self.schedule(Action::Increment(i));
+    }
+
+    pub fn schedule_decrement(&mut self, i: usize) {
+        self.schedule(Action::Decrement(i));
+    }
+
+    /// Sends `action` after a delay, replacing any delayed action still in flight.
+    ///
+    /// An aborted task never sends its `ExitProcessing`, so only the last one ends
+    /// Processing mode.
+    fn schedule(&mut self, action: Action) {
+        self.cancel_scheduled();
+        let Some(tx) = self.action_tx.clone() else {
+            error!("Cannot schedule {action}: no action handler registered");
+            return;
+        };
+        let delay = self.schedule_delay;
+        let due = tokio::time::Instant::now() + delay;
+        let task_action = action.clone();
+        let task = tokio::spawn(async move {
+            // A closed channel means the app is gone, so there is nobody left to tell
+            if tx.send(Action::EnterProcessing).is_err() {
+                return;
+            }
+            tokio::time::sleep_until(due).await;
+            let _ = tx
+                .send(task_action)
+                .and_then(|()| tx.send(Action::ExitProcessing));
+        });
+        self.next_task_id += 1;
+        self.scheduled.push(ScheduledTask {
+            id: self.next_task_id,
+            action,
+            due,
+            handle: task.abort_handle(),
+        });
+    }
+
+    /// Checks `input` with `validator` in a background task, which reports back with
+    /// `Action::InputValidated` and holds Processing mode until then.
+    fn validate(&mut self, validator: fn(&str) -> bool, input: String) {
+        let Some(tx) = self.action_tx.clone() else {
+            error!("Cannot validate {input}: no action handler registered");
+            return;
+        };
+        let due = tokio::time::Instant::now() + VALIDATION_DELAY;
+        tokio::spawn(async move {
+            if tx.send(Action::EnterProcessing).is_err() {
+                return;
+            }
+            tokio::time::sleep_until(due).await;
+            let valid = validator(&input);
+            let _ = tx
+                .send(Action::InputValidated(valid, input))
+                .and_then(|()| tx.send(Action::ExitProcessing));
+        });
+    }
+
+    /// Aborts every scheduled action still in flight.
+    pub fn cancel_scheduled(&mut self) {
+        for task in self.scheduled.drain(..) {
+            task.handle.abort();
+        }
+    }
+
+    /// Drops the queue entry for `action` once it has arrived from its task.
+    fn complete_scheduled(&mut self, action: &Action) {
+        let now = tokio::time::Instant::now();
+        // `coalesce` may have merged the task's action into a bigger one
+        let finishes = |task: &ScheduledTask| match (&task.action, action) {
+            (Action::Increment(i), Action::Increment(total))
+            | (Action::Decrement(i), Action::Decrement(total)) => i <= total,
+            _ => task.action == *action,
+        };
+        if let Some(index) = self
+            .scheduled
+            .iter()
+            .position(|task| finishes(task) && task.due <= now)
+        {
+            self.scheduled.remove(index);
+        }
+    }
+
+    /// Lists the scheduled actions and the time left on each at the bottom of `area`.
+    fn render_queue(&self, f: &mut Frame<'_>, area: Rect) {
+        let now = tokio::time::Instant::now();
+        let rows: Vec<Line> = self
+            .scheduled
+            .iter()
+            .map(|task| {
+                let left = task.due.saturating_duration_since(now);
+                Line::from(format!(
+                    "#{} {:?} in {:.1}s",
+                    task.id,
+                    task.action,
+                    left.as_secs_f64()
+                ))
+            })
+            .collect();
+        let inner = area.inner(Margin::new(1, 1));
+        let [_, queue_area] = Layout::vertical([
+            Constraint::Min(0),
+            Constraint::Length(rows.len() as u16 + 2),
+        ])
+        .areas(inner);
+        f.render_widget(Clear, queue_area);
+        f.render_widget(
+            List::new(rows).block(
+                Block::bordered()
+                    .title("Queue")
+                    .border_set(self.borders.list),
+            ),
+            queue_area,
+        );
+    }
+
+    pub fn increment(&mut self, i: usize) {
+        self.counter = match self.counter_max {
+            Some(max) => wrap_counter(self.counter as i128 + i as i128, max),
+            None => self.counter.saturating_add(i),
+        };
+        self.flash_counter(CounterChange::Up);
+        self.record_counter();
+        self.text_list_state.select_next();
+    }
+
+    fn flash_counter(&mut self, change: CounterChange) {
+        self.last_change = Some(change);
+        self.flash_remaining = self.flash_ticks;
+    }
+
+    /// Green or red for a moment after the counter goes up or down.
+    fn counter_style(&self) -> Style {
+        match self.last_change {
+            Some(CounterChange::Up) if self.flash_remaining > 0 => {
+                Style::default().fg(Color::Green)
+            }
+            Some(CounterChange::Down) if self.flash_remaining > 0 => {
+                Style::default().fg(Color::Red)
+            }
+            _ => Style::default(),
+        }
+    }
+
+    fn record_counter(&mut self) {
+        if self.counter_trend.len() == COUNTER_TREND_LEN {
+            self.counter_trend.pop_front();
+        }
+        self.counter_trend.push_back(self.counter as u64);
+    }
+
+    /// Shows the counter against `counter_max`, or its recent values when unbounded.
+    fn render_counter(&self, f: &mut Frame<'_>, area: Rect) {
+        match self.counter_max {
+            Some(max) => {
+                let ratio = if max == 0 {
+                    0.0
+                } else {
+                    (self.counter as f64 / max as f64).clamp(0.0, 1.0)
+                };
+                let gauge = LineGauge::default()
+                    .filled_style(Style::default().fg(self.theme().highlight))
+                    .label(format!("{}/{max}", self.counter))
+                    .ratio(ratio);
+                f.render_widget(gauge, area);
+            }
+            None => {
+                let data: Vec<u64> = self.counter_trend.iter().copied().collect();
+                let sparkline = Sparkline::default()
+                    .data(&data)
+                    .style(Style::default().fg(self.theme().highlight));
+                f.render_widget(sparkline, area);
+            }
+        }
+    }
+
+    pub fn decrement(&mut self, i: usize) {
+        self.counter = match self.counter_max {
+            Some(max) => wrap_counter(self.counter as i128 - i as i128, max),
+            None => self.counter.saturating_sub(i),
+        };
+        self.flash_counter(CounterChange::Down);
+        self.record_counter();
+        self.text_list_state.select_previous();
+    }
+
+    /// Edits the selected zone item if there is one, or the input box otherwise.
+    pub fn enter_insert(&mut self) {
+        if self.selected_menu == MenuItem::Zones && self.zone_widget_state.selected.is_some() {
+            self.edit_zone_item();
+        } else {
+            self.set_mode(Mode::Insert);
+        }
+    }
+
+    pub fn edit_zone_item(&mut self) {
+        if let Some(item) = self.zone_widget_state.selected {
+            let Some(zone) = self.zones.get(self.selected_zone) else {
+                return;
+            };
+            let input = Input::new(zone.value(item));
+            self.zone_widget_state.selected_mode = ItemMode::Insert;
+            self.zone_widget_state.edit_input = input;
+            self.zone_widget_state.input_kind = item.input_kind();
+            self.set_mode(Mode::Insert);
+        }
+    }
+
+    pub fn commit_zone_edit(&mut self) {
+        if let Some(item) = self.zone_widget_state.editing()
+            && let Some(zone) = self.zones.get(self.selected_zone)
+        {
+            let mut edited = zone.clone();
+            let result = edited
+                .set_value(item, self.zone_widget_state.edit_input.value())
+                .and_then(|()| self.check_link(self.selected_zone, &edited, item));
+            match result {
+                Ok(()) => self.zones[self.selected_zone] = edited,
+                Err(e) => self.send_action(Action::Error(e)),
+            }
+        }
+        self.cancel_zone_edit();
+    }
+
+    /// Rejects an `item` link of the zone at `index` that points at the zone itself,
+    /// or at a zone that links straight back to it the same way.
+    fn check_link(&self, index: usize, zone: &Zone, item: ZoneItem) -> Result<(), String> {
+        let link: fn(&Zone) -> i32 = match item {
+            ZoneItem::Name => return Ok(()),
+            ZoneItem::UpstreamZone => |zone| zone.prev_zone,
+            ZoneItem::DownstreamZone => |zone| zone.next_zone,
+        };
+        let own = index as i32;
+        if link(zone) == own {
+            return Err(format!("{item} can't be the zone itself"));
+        }
+        if let Some(other) = self.zone_link(link(zone))
+            && link(&self.zones[other]) == own
+        {
+            return Err(format!(
+                "{item} would loop, {} already links back",
+                self.zones[other].name
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn cancel_zone_edit(&mut self) {
+        self.zone_widget_state.edit_input.reset();
+        self.zone_widget_state.selected_mode = ItemMode::Normal;
+    }
+
+    /// Resolves a `prev_zone`/`next_zone` link to an index into `zones`.
+    ///
+    /// Negative or out-of-range links mean the zone has no neighbor.
+    pub fn zone_link(&self, link: i32) -> Option<usize> {
+        usize::try_from(link).ok().filter(|&i| i < self.zones.len())
+    }
+
+    /// Appends a new zone and selects it, linking it downstream of the
+    /// previously selected zone if that one had no downstream neighbor yet.
+    pub fn add_zone(&mut self) {
+        let index = self.zones.len();
+        let mut zone = Zone {
+            name: format!("Zone {}", index),
+            ..Zone::default()
+        };
+        let selected_is_tail = self
+            .zones
+            .get(self.selected_zone)
+            .is_some_and(|prev| self.zone_link(prev.next_zone).is_none());
+        if selected_is_tail {
+            self.zones[self.selected_zone].next_zone = index as i32;
+            zone.prev_zone = self.selected_zone as i32;
+        }
+        self.zones.push(zone);
+        self.selected_zone = index;
+    }
+
+    /// Splices a new zone in after the zone at `index`, between it and its next zone.
+    pub fn insert_zone_after(&mut self, index: usize) {
+        if index >= self.zones.len() {
+            return;
+        }
+        let new = self.zones.len();
+        let next = self.zone_link(self.zones[index].next_zone);
+        self.zones.push(Zone {
+            name: format!("Zone {}", new),
+            prev_zone: index as i32,
+            next_zone: next.map_or(-1, |next| next as i32),
+            ..Zone::default()
+        });
+        self.zones[index].next_zone = new as i32;
+        if let Some(next) = next {
+            self.zones[next].prev_zone = new as i32;
+        }
+        self.selected_zone = new;
+    }
+
+    /// Removes the zone at `index`, unlinking the zones that pointed at it and shifting
+    /// the links past it down so they keep pointing at the same zones.
+    pub fn remove_zone(&mut self, index: usize) {
+        if index >= self.zones.len() {
+            return;
+        }
+        self.zones.remove(index);
+        let removed = index as i32;
+        for zone in &mut self.zones {
+            for link in [&mut zone.prev_zone, &mut zone.next_zone] {
+                if *link == removed {
+                    *link = -1;
+                } else if *link > removed {
+                    *link -= 1;
+                }
+            }
+        }
+        if self.selected_zone > index {
+            self.selected_zone -= 1;
+        }
+        self.selected_zone = self.selected_zone.min(self.zones.len().saturating_sub(1));
+    }
+
+    pub fn save_zones(&self, path: impl AsRef<Path>) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.zones)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Restores the counter and input pane size saved at `path` by a previous run,
+    /// and saves them there again on quit.
+    ///
+    /// A missing file starts fresh, as does one that can't be read, after reporting it.
+    pub fn load_persisted(mut self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        match std::fs::read_to_string(&path) {
+            Ok(json) => match serde_json::from_str::<Persisted>(&json) {
+                Ok(persisted) => {
+                    self.counter = persisted.counter;
+                    if let Some(input_pane) = persisted.input_pane {
+                        self.input_pane =
+                            input_pane.clamp(*INPUT_PANE_RANGE.start(), *INPUT_PANE_RANGE.end());
+                    }
+                }
+                Err(e) => self.report_error(format!("Ignoring {}: {e}", path.display())),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => self.report_error(format!("Ignoring {}: {e}", path.display())),
+        }
+        self.persist_path = Some(path);
+        self
+    }
+
+    /// Writes the counter and input pane size to the path given to `load_persisted`,
+    /// if any.
+    pub fn save_persisted(&self) -> Result<()> {
+        let Some(path) = &self.persist_path else {
+            return Ok(());
+        };
+        let persisted = Persisted {
+            version: PERSIST_VERSION,
+            counter: self.counter,
+            input_pane: Some(self.input_pane),
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&persisted)?)?;
+        Ok(())
+    }
+
+    /// Replaces the zones with the ones stored at `path`.
+    ///
+    /// The current zones are kept if the file can't be read or its links are invalid.
+    pub fn load_zones(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let json = std::fs::read_to_string(path)?;
+        let zones: Vec<Zone> = serde_json::from_str(&json)?;
+        validate_links(&zones)?;
+        self.zones = zones;
+        self.selected_zone = 0;
+        Ok(())
+    }
+
+    pub fn next_zone(&mut self) {
+        if let Some(next) = self.follow_link(|zone| zone.next_zone) {
+            self.selected_zone = next;
+        }
+    }
+
+    pub fn previous_zone(&mut self) {
+        if let Some(prev) = self.follow_link(|zone| zone.prev_zone) {
+            self.selected_zone = prev;
+        }
+    }
+
+    /// Selects the first zone named `name`, ignoring case.
+    pub fn goto_zone(&mut self, name: &str) -> std::result::Result<(), String> {
+        let index = self
+            .zones
+            .iter()
+            .position(|zone| zone.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("No zone named {name}"))?;
+        self.selected_zone = index;
+        Ok(())
+    }
+
+    /// The first enabled zone reached by following `link` from the selected zone.
+    fn follow_link(&self, link: impl Fn(&Zone) -> i32) -> Option<usize> {
+        let mut current = self.selected_zone;
+        // Bounded, so a loop of disabled zones can't spin forever
+        for _ in 0..self.zones.len() {
+            current = self.zone_link(link(self.zones.get(current)?))?;
+            if self.zones[current].enabled {
+                return Some(current);
+            }
+        }
+        None
+    }
+
+    pub fn toggle_zone_enabled(&mut self, index: usize) {
+        if let Some(zone) = self.zones.get_mut(index) {
+            zone.enabled = !zone.enabled;
+        }
+    }
+
+    pub fn next_tab(&mut self) {
+        self.selected_menu = self.selected_menu.next();
+    }
+
+    pub fn previous_tab(&mut self) {
+        self.selected_menu = self.selected_menu.previous();
+    }
+}
+
+impl Component for Home {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.action_tx = Some(tx);
+        Ok(())
+    }
+
+    fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        if !config.themes.is_empty() {
+            self.themes = config.themes.clone();
+            self.theme = 0;
+        }
+        // Bindings from the config fill in whatever the builder didn't set
+        if let Some(bindings) = config.keybindings.get(&app::Mode::Home) {
+            for (keys, action) in bindings.iter() {
+                match keys.as_slice() {
+                    [key] => self.keymap.entry(*key).or_insert_with(|| action.clone()),
+                    _ => self
+                        .chords
+                        .entry(keys.clone())
+                        .or_insert_with(|| action.clone()),
+                };
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_events(&mut self, event: Option<Event>) -> Result<Option<Action>> {
+        self.handling_input = true;
+        let action = match event {
+            Some(Event::Key(key)) => self.handle_key_events(key),
+            Some(Event::Mouse(mouse)) => self.handle_mouse_events(mouse),
+            Some(Event::Paste(text)) => self.handle_paste(text),
+            _ => Ok(None),
+        };
+        self.handling_input = false;
+        if let Ok(Some(action)) = &action {
+            self.record_action(action);
+        }
+        action
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        self.needs_render = true;
+        self.last_events.push(key);
+        if self.show_quit_dialog || self.show_clear_dialog {
+            let (confirm, cancel) = if self.show_quit_dialog {
+                (Action::Quit, Action::CancelQuit)
+            } else {
+                (Action::ClearHistory, Action::CancelClearHistory)
+            };
+            let action = match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => confirm,
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => cancel,
+                // Swallowed, or the app would fall back to the bindings behind the dialog
+                _ => Action::Update,
+            };
+            return Ok(Some(action));
+        }
+        if self.mode == Mode::Normal
+            && let Some(action) = self.chord_action(key)
+        {
+            return Ok(Some(action));
+        }
+        let action = match self.mode {
+            Mode::Normal if let Some(action) = self.bound_action(key) => action.clone(),
+            Mode::Normal => {
+                match key.code {
+                    // A leading zero isn't a count, vim-style
+                    KeyCode::Char(c @ '0'..='9') if c != '0' || self.pending_count.is_some() => {
+                        let digit = c.to_digit(10).unwrap_or_default() as usize;
+                        let count = self.pending_count.unwrap_or_default();
+                        self.pending_count = Some(count.saturating_mul(10).saturating_add(digit));
+                    }
+                    // Dropping the zone item selection sends `/` back to the input box
+                    KeyCode::Esc => {
+                        self.pending_count = None;
+                        self.zone_widget_state.selected = None;
+                    }
+                    _ => (),
+                }
+                return Ok(None);
+            }
+            // Looked up here rather than left to the app, so they get recorded
+            Mode::Processing => return Ok(self.bound_action(key).cloned()),
+            Mode::Insert if self.zone_widget_state.editing().is_some() => match key.code {
+                KeyCode::Esc => Action::EnterNormal,
+                KeyCode::Enter => {
+                    let before = self.snapshot();
+                    self.commit_zone_edit();
+                    self.record(before);
+                    Action::EnterNormal
+                }
+                KeyCode::Char(c) if !self.zone_widget_state.input_kind.accepts(c) => Action::Update,
+                _ => {
+                    let state = &mut self.zone_widget_state;
+                    state
+                        .edit_input
+                        .handle_event(&crossterm::event::Event::Key(key));
+                    Action::Update
+                }
+            },
+            Mode::Filter => match key.code {
+                KeyCode::Esc => Action::ClearFilter,
+                KeyCode::Enter => Action::EnterNormal,
+                _ => {
+                    self.filter_input
+                        .handle_event(&crossterm::event::Event::Key(key));
+                    Action::UpdateFilter(self.filter_input.value().to_string())
+                }
+            },
+            Mode::Insert => match key.code {
+                KeyCode::Esc => Action::EnterNormal,
+                KeyCode::Up => {
+                    self.recall_previous();
+                    Action::Update
+                }
+                KeyCode::Down => {
+                    self.recall_next();
+                    Action::Update
+                }
+                // Handled even without a completion, or the bindings would switch tabs
+                KeyCode::Tab => {
+                    if let Some(completed) = self
+                        .completions()
+                        .first()
+                        .map(|name| format!(":goto {name}"))
+                    {
+                        self.input = Input::new(completed);
+                    }
+                    Action::Update
+                }
+                KeyCode::Enter => {
+                    self.recall = None;
+                    let value = self.input.value().to_string();
+                    if value.trim().is_empty() && !self.allow_empty_input {
+                        self.send_action(Action::EmptyInput);
+                    } else {
+                        self.send_action(Action::CompleteInput(value));
+                        self.input.reset();
+                    }
+                    Action::EnterNormal
+                }
+                _ => {
+                    self.input.handle_event(&crossterm::event::Event::Key(key));
+                    Action::Update
+                }
+            },
+        };
+        Ok(Some(action))
+    }
+
+    fn handle_mouse_events(&mut self, mouse: MouseEvent) -> Result<Option<Action>> {
+        if self.mode != Mode::Normal
+            || self.show_help
+            || self.show_quit_dialog
+            || self.show_clear_dialog
+        {
+            return Ok(None);
+        }
+        let position = Position::new(mouse.column, mouse.row);
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let zone_item = self
+                    .zone_widget_state
+                    .item_areas
+                    .iter()
+                    .find(|(_, area)| area.contains(position))
+                    .map(|(item, _)| *item);
+                if self.tab_bar_area.contains(position)
+                    && let Some(item) = MenuItem::at_column(self.tab_bar_area.x, position.x)
+                {
+                    self.selected_menu = item;
+                } else if self.selected_menu == MenuItem::Zones
+                    && let Some(item) = zone_item
+                {
+                    self.zone_widget_state.selected = Some(item);
+                    self.focus = Focus::Zones;
+                } else if let Some(index) = self.history_row_at(position) {
+                    self.text_list_state.select(Some(index));
+                    self.focus = Focus::List;
+                } else {
+                    return Ok(None);
+                }
+            }
+            // Scroll the way the wheel moves on screen, whichever way the list is drawn
+            MouseEventKind::ScrollUp if self.list_area.contains(position) => {
+                match self.list_direction {
+                    ListDirection::TopToBottom => self.scroll_history_up(),
+                    ListDirection::BottomToTop => self.scroll_history_down(),
+                }
+            }
+            MouseEventKind::ScrollDown if self.list_area.contains(position) => {
+                match self.list_direction {
+                    ListDirection::TopToBottom => self.scroll_history_down(),
+                    ListDirection::BottomToTop => self.scroll_history_up(),
+                }
+            }
+            _ => return Ok(None),
+        }
+        self.needs_render = true;
+        Ok(Some(Action::Update))
+    }
+
+    fn handle_paste(&mut self, text: String) -> Result<Option<Action>> {
+        // The inputs are single line, so pasted line breaks become spaces
+        let text = text.replace("\r\n", " ").replace(['\r', '\n'], " ");
+        let paste = |input: &mut Input, kind: InputKind| {
+            for c in text.chars().filter(|&c| kind.accepts(c)) {
+                input.handle(InputRequest::InsertChar(c));
+            }
+        };
+        let action = match self.mode {
+            Mode::Insert if self.zone_widget_state.editing().is_some() => {
+                let state = &mut self.zone_widget_state;
+                paste(&mut state.edit_input, state.input_kind);
+                Action::Update
+            }
+            Mode::Insert => {
+                paste(&mut self.input, InputKind::Text);
+                Action::Update
+            }
+            Mode::Filter => {
+                paste(&mut self.filter_input, InputKind::Text);
+                Action::UpdateFilter(self.filter_input.value().to_string())
+            }
+            Mode::Normal | Mode::Processing => return Ok(None),
+        };
+        self.needs_render = true;
+        Ok(Some(action))
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        let undoable = is_undoable(&action);
+        let before = undoable.then(|| self.snapshot());
+        *self.action_counts.entry(action.to_string()).or_default() += 1;
+        if self.recent.len() == MAX_RECENT_ACTIONS {
+            self.recent.remove(0);
+        }
+        self.recent.push(action.clone());
+        // The tickers are only diagnostics, so counting them doesn't warrant a redraw
+        if !matches!(action, Action::Tick | Action::Render) || self.show_action_counts {
+            self.needs_render = true;
+        }
+        if self.read_only && self.is_mutating(&action) {
+            self.show_message(format!("Read-only, ignoring {action}"));
+            return Ok(None);
+        }
+        match action {
+            Action::Tick => self.tick(),
+            Action::Refresh => self.refresh(),
+            Action::Suspend => {
+                self.suspended.get_or_insert(self.mode);
+            }
+            Action::Resume => {
+                if let Some(mode) = self.suspended.take() {
+                    self.set_mode(mode);
+                }
+            }
+            Action::Resize(width, height) => self.size = Some(Size::new(width, height)),
+            Action::Render => self.render_tick(),
+            Action::Help => self.show_help = true,
+            Action::ToggleShowHelp => self.show_help = !self.show_help,
+            Action::ToggleWrap => self.wrap = !self.wrap,
+            Action::GrowInput => self.resize_input_pane(INPUT_PANE_STEP as i16),
+            Action::ShrinkInput => self.resize_input_pane(-(INPUT_PANE_STEP as i16)),
+            Action::ToggleReadOnly => {
+                self.read_only = !self.read_only;
+                self.show_message(
+                    if self.read_only {
+                        "Read-only on"
+                    } else {
+                        "Read-only off"
+                    }
+                    .to_owned(),
+                );
+            }
+            Action::SetTickRate(tick_rate) if !tick_rate.is_zero() => {
+                self.tick_rate = tick_rate;
+                self.ticks_since = None;
+            }
+            Action::ToggleActionCounts => self.show_action_counts = !self.show_action_counts,
+            Action::ToggleRecentActions => self.show_recent_actions = !self.show_recent_actions,
+            Action::ToggleFrameTimes => self.show_frame_times = !self.show_frame_times,
+            Action::Quit => {
+                // Nothing is left to receive what the tasks would send
+                self.cancel_scheduled();
+                if !self.read_only
+                    && let Err(e) = self.save_persisted()
+                {
+                    error!("Failed to save state: {e}");
+                }
+            }
+            Action::RequestQuit if !self.confirm_quit => return Ok(Some(Action::Quit)),
+            Action::RequestQuit => self.show_quit_dialog = true,
+            Action::CancelQuit => self.show_quit_dialog = false,
+            Action::RequestClearHistory if !self.confirm_clear_history => {
+                return Ok(Some(Action::ClearHistory));
+            }
+            Action::RequestClearHistory => self.show_clear_dialog = true,
+            Action::CancelClearHistory => self.show_clear_dialog = false,
+            Action::ClearHistory => {
+                self.show_clear_dialog = false;
+                self.clear_history();
+            }
+            Action::CycleFocus => self.focus = self.focus.next(),
+            Action::NextTab => self.next_tab(),
+            Action::PrevTab => self.previous_tab(),
+            Action::SelectTab(number) => {
+                if let Some(item) = number.checked_sub(1).and_then(MenuItem::from_repr) {
+                    self.selected_menu = item;
+                }
+            }
+            Action::EnterInsert | Action::EditZoneItem if self.mode == Mode::Normal => {
+                self.enter_insert()
+            }
+            Action::SelectNextItem if !self.is_typing() => {
+                self.zone_widget_state.select_next();
+                self.focus = Focus::Zones;
+            }
+            Action::SelectPrevItem if !self.is_typing() => {
+                self.zone_widget_state.select_previous();
+                self.focus = Focus::Zones;
+            }
+            Action::AddZone if self.mode == Mode::Normal => self.add_zone(),
+            Action::InsertZoneAfter(index) if self.mode == Mode::Normal => {
+                self.insert_zone_after(index)
+            }
+            Action::ToggleZoneEnabled(index) if self.mode == Mode::Normal => {
+                self.toggle_zone_enabled(index)
+            }
+            Action::ToggleSelectedZoneEnabled if self.mode == Mode::Normal => {
+                self.toggle_zone_enabled(self.selected_zone)
+            }
+            Action::RemoveZone(index) if self.mode == Mode::Normal => self.remove_zone(index),
+            Action::RemoveSelectedZone if self.mode == Mode::Normal => {
+                self.remove_zone(self.selected_zone)
+            }
+            Action::Save => {
+                let path = get_data_dir().join(ZONES_FILE);
+                match self.save_zones(&path) {
+                    Ok(()) => self.show_message(format!("Saved zones to {}", path.display())),
+                    Err(e) => self.report_error(format!("Failed to save zones: {e}")),
+                }
+            }
+            Action::Load if self.mode == Mode::Normal => {
+                let path = get_data_dir().join(ZONES_FILE);
+                match self.load_zones(&path) {
+                    Ok(()) => self.show_message(format!("Loaded zones from {}", path.display())),
+                    Err(e) => self.report_error(format!("Failed to load zones: {e}")),
+                }
+            }
+            Action::NextZone if self.mode == Mode::Normal => self.next_zone(),
+            Action::PrevZone if self.mode == Mode::Normal => self.previous_zone(),
+            Action::GotoZone(name) => {
+                if let Err(e) = self.goto_zone(&name) {
+                    self.send_action(Action::Error(e));
+                }
+            }
+            Action::IncrementSingle if !self.is_typing() => {
+                let count = self.pending_count.take().unwrap_or(1);
+                self.increment(count.saturating_mul(self.step))
+            }
+            Action::DecrementSingle if !self.is_typing() => {
+                let count = self.pending_count.take().unwrap_or(1);
+                self.decrement(count.saturating_mul(self.step))
+            }
+            Action::SetStep(0) => self.report_error("The step must be at least 1".to_owned()),
+            Action::SetStep(step) => self.step = step,
+            Action::ScheduleIncrement if !self.is_typing() => self.schedule_increment(1),
+            Action::ScheduleDecrement if !self.is_typing() => self.schedule_decrement(1),
+            Action::Increment(i) => {
+                self.complete_scheduled(&action);
+                self.increment(i)
+            }
+            Action::Decrement(i) => {
+                self.complete_scheduled(&action);
+                self.decrement(i)
+            }
+            Action::CompleteInput(s) => match s.strip_prefix(':') {
+                Some(command) => match parse_command(command) {
+                    Ok(action) => self.send_action(action),
+                    Err(e) => self.send_action(Action::Error(e)),
+                },
+                None => match self.validator {
+                    Some(validator) => self.validate(validator, s),
+                    None => self.add(s),
+                },
+            },
+            Action::InputValidated(true, s) => self.add(s),
+            Action::InputValidated(false, s) => {
+                self.send_action(Action::Error(format!("Rejected input: {s}")))
+            }
+            Action::SetCounter(value) => {
+                self.counter = self.counter_max.map_or(value, |max| value.min(max));
+                self.record_counter();
+            }
+            Action::EmptyInput => {
+                self.show_message("Nothing to add, the input is empty".to_owned())
+            }
+            // Whatever acts on the history entry gives the list focus, so its highlight shows
+            Action::DeleteSelected if self.mode == Mode::Normal => {
+                self.delete_selected();
+                self.focus = Focus::List;
+            }
+            Action::MoveEntryUp if self.mode == Mode::Normal => {
+                self.move_selected(true);
+                self.focus = Focus::List;
+            }
+            Action::MoveEntryDown if self.mode == Mode::Normal => {
+                self.move_selected(false);
+                self.focus = Focus::List;
+            }
+            Action::CopySelected if self.mode == Mode::Normal => self.copy_selected(),
+            Action::GotoTop if self.mode == Mode::Normal => self.goto_top(),
+            // With a count, vim-style `5G` jumps to that entry instead
+            Action::GotoBottom if self.mode == Mode::Normal => match self.pending_count.take() {
+                Some(index) => self.goto_index(index),
+                None => self.goto_bottom(),
+            },
+            Action::ScrollHistoryUp => {
+                self.scroll_history_up();
+                self.focus = Focus::List;
+            }
+            Action::PageUpList => {
+                self.page_list(true);
+                self.focus = Focus::List;
+            }
+            Action::PageDownList => {
+                self.page_list(false);
+                self.focus = Focus::List;
+            }
+            Action::ScrollHistoryDown => {
+                self.scroll_history_down();
+                self.focus = Focus::List;
+            }
+            Action::ScrollUp => self.scroll_up(),
+            Action::ScrollDown => self.scroll_down(),
+            Action::Error(e) => self.notify(Severity::default(), e),
+            Action::Notify(severity, message) => self.notify(severity, message),
+            Action::ToggleErrors => self.show_errors = !self.show_errors,
+            Action::CycleTheme => self.cycle_theme(),
+            Action::CycleTextColor => self.cycle_text_color(),
+            Action::DumpState => self.write_state_dump(),
+            Action::StartRecording(path) => {
+                if let Err(e) = self.start_recording(&path) {
+                    self.report_error(format!("Failed to record to {}: {e}", path.display()));
+                }
+            }
+            Action::StopRecording => {
+                if let Err(e) = self.stop_recording() {
+                    self.report_error(format!("Failed to save the recording: {e}"));
+                }
+            }
+            Action::Replay(path) => {
+                if let Err(e) = self.replay(&path) {
+                    self.report_error(format!("Failed to replay {}: {e}", path.display()));
+                }
+            }
+            Action::EnterNormal => {
+                if self.mode == Mode::Insert {
+                    self.leave_insert();
+                }
+                self.set_mode(Mode::Normal);
+            }
+            Action::EnterFilter if self.mode == Mode::Normal => {
+                self.filter_input = Input::new(self.filter.clone());
+                self.set_mode(Mode::Filter);
+            }
+            Action::UpdateFilter(filter) => self.set_filter(filter),
+            Action::ClearFilter => self.clear_filter(),
+            Action::EnterInsert if self.mode != Mode::Filter => self.set_mode(Mode::Insert),
+            Action::EnterProcessing => self.set_mode(Mode::Processing),
+            Action::ExitProcessing => self.set_mode(self.prev_mode),
+            Action::Undo if !self.is_typing() => self.undo(),
+            Action::Redo if !self.is_typing() => self.redo(),
+            _ => (),
+        }
+        if let Some(before) = before {
+            self.record(before);
+        }
+        Ok(None)
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.needs_render
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, rect: Rect) -> Result<()> {
+        self.needs_render = false;
+        if rect.width < MIN_WIDTH || rect.height < MIN_HEIGHT {
+            let warning = format!("Terminal too small (need {MIN_WIDTH}x{MIN_HEIGHT})");
+            let lines = (warning.len() as u16).div_ceil(rect.width.max(1));
+            // Pad rather than size the area, word wrapping can take an extra line
+            let padding = Padding::top(rect.height.saturating_sub(lines) / 2);
+            let paragraph = Paragraph::new(warning)
+                .block(Block::new().padding(padding))
+                .centered()
+                .wrap(Wrap { trim: true });
+            f.render_widget(paragraph, rect);
+            return Ok(());
+        }
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(rect);
+        let [content, side] = if self.stacked_layout(rect) {
+            Layout::vertical(self.body_constraints()).areas(body)
+        } else {
+            Layout::horizontal(self.body_constraints()).areas(body)
+        };
+        let [tab_area, main_area] =
+            Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(content);
+        let [input_area, mut list_area] =
+            Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(side);
+
+        let mut text: Vec<Line> = self
+            .text
+            .iter()
+            .enumerate()
+            .map(|(i, l)| self.stamped_line(i, l))
+            .collect();
+        text.insert(0, "".into());
+        text.insert(
+            0,
+            "Type into input and hit enter to display here".dim().into(),
+        );
+        text.insert(0, "".into());
+        text.insert(0, format!("Render Ticker: {}", self.render_ticker).into());
+        text.insert(0, format!("App Ticker: {}", self.app_ticker).into());
+        text.insert(
+            0,
+            Line::styled(format!("Counter: {}", self.counter), self.counter_style()),
+        );
+        text.insert(0, "".into());
+        text.insert(
+            0,
+            Line::from(vec![
+                "Press ".into(),
+                Span::styled("j", Style::default().fg(Color::Red)),
+                " or ".into(),
+                Span::styled("k", Style::default().fg(Color::Red)),
+                " to ".into(),
+                Span::styled("increment", Style::default().fg(Color::Yellow)),
+                " or ".into(),
+                Span::styled("decrement", Style::default().fg(Color::Yellow)),
+                ".".into(),
+            ]),
+        );
+        text.insert(0, "".into());
+
+        let mut paragraph = Paragraph::new(text);
+        if self.wrap {
+            paragraph = paragraph.wrap(Wrap { trim: false });
+        }
+        let main_block = Block::default()
+            .title("ratatui async template")
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_style(match self.mode {
+                Mode::Processing => Style::default().fg(Color::Yellow),
+                _ => Style::default(),
+            })
+            .border_set(self.borders.main);
+        let main_inner = main_block.inner(main_area);
+        let lines = paragraph.line_count(main_inner.width) as u16;
+        self.max_scroll = lines.saturating_sub(main_inner.height);
+        self.scroll = self.scroll.min(self.max_scroll);
+        f.render_widget(
+            paragraph
+                .block(main_block)
+                .style(Style::default().fg(self.text_color.unwrap_or(self.theme().text)))
+                .alignment(Alignment::Center)
+                .scroll((self.scroll, 0)),
+            main_area,
+        );
+        if self.max_scroll > 0 {
+            let mut scrollbar_state =
+                ScrollbarState::new(self.max_scroll as usize).position(self.scroll as usize);
+            f.render_stateful_widget(
+                Scrollbar::new(ScrollbarOrientation::VerticalRight),
+                main_area.inner(Margin::new(0, 1)),
+                &mut scrollbar_state,
+            );
+        }
+        if self.mode == Mode::Processing && !self.scheduled.is_empty() {
+            self.render_queue(f, main_area);
+        }
+        let width = input_area.width.max(3) - 3; // keep 2 for borders and 1 for cursor
+        let scroll = self.input.visual_scroll(width as usize);
+        let input = Paragraph::new(self.input.value())
+            .style(match self.mode {
+                Mode::Insert => Style::default().fg(Color::Yellow),
+                _ => Style::default(),
+            })
+            .scroll((0, scroll as u16))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_set(self.borders.input)
+                    .title(Line::from(vec![
+                        Span::raw("Enter Input Mode "),
+                        Span::styled("(Press ", Style::default().fg(Color::DarkGray)),
+                        Span::styled(
+                            "/",
+                            Style::default()
+                                .add_modifier(Modifier::BOLD)
+                                .fg(Color::Gray),
+                        ),
+                        Span::styled(" to start, ", Style::default().fg(Color::DarkGray)),
+                        Span::styled(
+                            "ESC",
+                            Style::default()
+                                .add_modifier(Modifier::BOLD)
+                                .fg(Color::Gray),
+                        ),
+                        Span::styled(" to finish)", Style::default().fg(Color::DarkGray)),
+                    ])),
+            );
+        f.render_widget(input, input_area);
+        if self.mode == Mode::Insert {
+            f.set_cursor_position(input_cursor(&self.input, input_area, scroll))
+        }
+
+        self.render_footer(f, footer);
+
+        match self.selected_menu {
+            MenuItem::Zones => {
+                let chains = zone_chains(&self.zones).len() as u16;
+                let [chain_area, zone_area] =
+                    Layout::vertical([Constraint::Length(chains), Constraint::Min(0)])
+                        .areas(tab_area);
+                f.render_widget(
+                    ZoneChainWidget::new(&self.zones, self.selected_zone),
+                    chain_area,
+                );
+                // Too short for every item inside the border, so fall back to one line
+                self.zone_widget_state
+                    .set_compact(zone_area.height < ZONE_WIDGET_HEIGHT);
+                if let Some(zone) = self.zones.get(self.selected_zone) {
+                    f.render_stateful_widget_ref(
+                        ZoneWidget::new(zone, &self.zones)
+                            .border_set(self.borders.zone)
+                            .highlight_style(self.theme().zone_highlight),
+                        zone_area,
+                        &mut self.zone_widget_state,
+                    );
+                }
+            }
+            MenuItem::Sensors => f.render_stateful_widget_ref(
+                SensorWidget::new(
+                    &self.sensors,
+                    self.selected_menu
+                        .tab_block(self.theme(), self.borders.tabs),
+                ),
+                tab_area,
+                &mut self.sensor_state,
+            ),
+            MenuItem::Motors => f.render_stateful_widget_ref(
+                MotorWidget::new(
+                    &self.motors,
+                    self.selected_menu
+                        .tab_block(self.theme(), self.borders.tabs),
+                ),
+                tab_area,
+                &mut self.motor_state,
+            ),
+            MenuItem::IO => f.render_stateful_widget_ref(
+                IoWidget::new(
+                    &self.io_points,
+                    self.selected_menu
+                        .tab_block(self.theme(), self.borders.tabs),
+                ),
+                tab_area,
+                &mut self.io_state,
+            ),
+            MenuItem::Misc => {
+                let entries = vec![
+                    ("Counter", self.counter.to_string()),
+                    ("Zones", self.zones.len().to_string()),
+                    ("History", self.text.len().to_string()),
+                    ("App Ticker", self.app_ticker.to_string()),
+                    (
+                        "Tick Rate",
+                        match self.measured_tick_rate(Instant::now()) {
+                            Some(rate) => {
+                                format!("{rate:.1}/s (every {}ms)", self.tick_rate.as_millis())
+                            }
+                            None => format!("every {}ms", self.tick_rate.as_millis()),
+                        },
+                    ),
+                    ("Render Ticker", self.render_ticker.to_string()),
+                ];
+                f.render_stateful_widget_ref(
+                    MiscWidget::new(
+                        entries,
+                        self.selected_menu
+                            .tab_block(self.theme(), self.borders.tabs),
+                    ),
+                    tab_area,
+                    &mut self.misc_state,
+                );
+            }
+        }
+
+        let [title_area, counter_area] =
+            Layout::horizontal([Constraint::Min(0), Constraint::Length(COUNTER_WIDTH)])
+                .areas(header.inner(Margin::new(1, 0)));
+        f.render_widget(self.selected_menu.tab_bar(self.theme()), title_area);
+        self.tab_bar_area = title_area;
+        self.render_counter(f, counter_area);
+
+        if self.mode == Mode::Filter || !self.filter.is_empty() {
+            let [filter_area, rest] =
+                Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(list_area);
+            list_area = rest;
+            let width = filter_area.width.max(3) - 3; // keep 2 for borders and 1 for cursor
+            let scroll = self.filter_input.visual_scroll(width as usize);
+            let filter = Paragraph::new(self.filter_input.value())
+                .style(match self.mode {
+                    Mode::Filter => Style::default().fg(Color::Yellow),
+                    _ => Style::default(),
+                })
+                .scroll((0, scroll as u16))
+                .block(
+                    Block::bordered()
+                        .border_set(self.borders.input)
+                        .title("Filter"),
+                );
+            f.render_widget(filter, filter_area);
+            if self.mode == Mode::Filter {
+                f.set_cursor_position(input_cursor(&self.filter_input, filter_area, scroll))
+            }
+        }
+
+        let visible = self.visible_history();
+        let list = List::new(
+            visible
+                .iter()
+                .map(|&i| self.stamped_line(i, &self.text_list[i])),
+        )
+        .block(
+            Block::bordered()
+                .border_set(self.borders.list)
+                .title("Fight!"),
+        )
+        .style(Style::new().white())
+        .highlight_style(self.list_highlight())
+        .highlight_symbol(self.highlight_symbol.as_str())
+        .repeat_highlight_symbol(true)
+        .direction(self.list_direction);
+        f.render_stateful_widget(list, list_area, &mut self.text_list_state);
+        self.list_area = list_area;
+        self.render_completions(f, input_area);
+
+        if self.show_help {
+            self.render_table_popup(f, rect, "Key Bindings", ["Key", "Action"], self.help_rows());
+        }
+
+        if self.show_action_counts {
+            self.render_table_popup(
+                f,
+                rect,
+                "Action Counts",
+                ["Count", "Action"],
+                self.count_rows(),
+            );
+        }
+
+        if self.show_recent_actions {
+            // Ticks and renders would crowd out everything else
+            let lines: Vec<Line> = self
+                .recent
+                .iter()
+                .rev()
+                .filter(|action| !matches!(action, Action::Tick | Action::Render))
+                .map(|action| format!("{action:?}").into())
+                .collect();
+            let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16;
+            let height = lines.len() as u16;
+            render_modal(
+                f,
+                rect,
+                "Recent Actions".bold().into(),
+                Style::default().fg(self.theme().border),
+                Size::new(width, height),
+                Paragraph::new(lines),
+            );
+        }
+
+        if self.show_frame_times {
+            // Spikes are frames where something held up rendering. The newest frames
+            // are drawn from the right edge, so a narrow popup cuts off the oldest ones.
+            let data: Vec<u64> = self
+                .frame_times
+                .iter()
+                .rev()
+                .map(|time| time.as_micros() as u64)
+                .collect();
+            let slowest = self.frame_times.iter().max().copied().unwrap_or_default();
+            render_modal(
+                f,
+                rect,
+                format!("Frame Times (max {slowest:.1?})").bold().into(),
+                Style::default().fg(self.theme().border),
+                Size::new(FRAME_TIMES_LEN as u16, 8),
+                Sparkline::default()
+                    .data(&data)
+                    .direction(RenderDirection::RightToLeft)
+                    .style(Style::default().fg(self.theme().highlight)),
+            );
+        }
+
+        if self.show_errors {
+            let lines: Vec<Line> = if self.errors.is_empty() {
+                vec!["No errors".dim().into()]
+            } else {
+                self.errors
+                    .iter()
+                    .rev()
+                    .map(|e| e.as_str().into())
+                    .collect()
+            };
+            let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16;
+            let height = lines.len() as u16;
+            render_modal(
+                f,
+                rect,
+                "Errors".bold().into(),
+                Style::default().fg(Color::Red),
+                Size::new(width, height),
+                Paragraph::new(lines),
+            );
+        }
+
+        if self.suspended.is_some() {
+            let rect = centered_area(rect, 13, 3);
+            f.render_widget(Clear, rect);
+            f.render_widget(
+                Paragraph::new("Suspended").centered().block(
+                    Block::bordered().border_style(Style::default().fg(self.theme().border)),
+                ),
+                rect,
+            );
+        }
+
+        if self.show_quit_dialog {
+            self.render_confirm_dialog(f, rect, "Quit? (y/n)");
+        }
+
+        if self.show_clear_dialog {
+            self.render_confirm_dialog(f, rect, "Clear history? (y/n)");
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use ratatui::backend::TestBackend;
+
+    use super::*;
+    use crate::{action::coalesce, components::centered_rect};
+
+    fn draw(home: &mut Home, terminal: &mut Terminal<TestBackend>) {
+        terminal.draw(|f| home.draw(f, f.area()).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_zone_widget_state_survives_draw() {
+        let mut home = Home::new();
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        home.update(Action::SelectNextItem).unwrap();
+        home.update(Action::SelectNextItem).unwrap();
+
+        draw(&mut home, &mut terminal);
+        draw(&mut home, &mut terminal);
+
+        // The selected item is still the highlighted one after the second draw
+        let highlight = home.theme().zone_highlight.add_modifier;
+        let buf = terminal.backend().buffer();
+        let row = |label: &str| {
+            (0..buf.area.height)
+                .find(|&y| {
+                    let line: String = (1..1 + label.len() as u16)
+                        .map(|x| buf[(x, y)].symbol())
+                        .collect();
+                    line == label
+                })
+                .unwrap()
+        };
+        assert!(buf[(1, row("Upstream Zone"))].modifier.contains(highlight));
+        assert!(!buf[(1, row("Name"))].modifier.contains(highlight));
+    }
+
+    fn type_keys(home: &mut Home, keys: &str) {
+        for c in keys.chars() {
+            home.handle_key_events(KeyEvent::from(KeyCode::Char(c)))
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_zone_edit_commits_on_enter() {
+        let mut home = Home::new();
+        home.zone_widget_state.selected = Some(ZoneItem::Name);
+        home.update(Action::EditZoneItem).unwrap();
+        assert_eq!(home.mode, Mode::Insert);
+
+        type_keys(&mut home, "A");
+        home.handle_key_events(KeyEvent::from(KeyCode::Enter))
+            .unwrap();
+
+        assert_eq!(home.zones[0].value(ZoneItem::Name), "ZoneA");
+        assert!(home.zone_widget_state.editing().is_none());
+    }
+
+    #[test]
+    fn test_zone_edit_cancels_on_esc() {
+        let mut home = Home::new();
+        home.zone_widget_state.selected = Some(ZoneItem::DownstreamZone);
+        home.update(Action::EditZoneItem).unwrap();
+
+        home.handle_key_events(KeyEvent::from(KeyCode::Backspace))
+            .unwrap();
+        type_keys(&mut home, "4");
+        let action = home
+            .handle_key_events(KeyEvent::from(KeyCode::Esc))
+            .unwrap();
+        home.update(action.unwrap()).unwrap();
+
+        assert_eq!(home.zones[0].value(ZoneItem::DownstreamZone), "-1");
+        assert!(home.zone_widget_state.editing().is_none());
+    }
+
+    #[test]
+    fn test_zone_widget_highlights_selected_item() {
+        let zone = Zone::default();
+        let widget = ZoneWidget::new(&zone, &[]);
+        let mut state = ZoneWidgetState {
+            selected: Some(ZoneItem::UpstreamZone),
+            ..Default::default()
+        };
+        let area = Rect::new(0, 0, 26, 8);
+        let mut buf = Buffer::empty(area);
+        widget.render_ref(area, &mut buf, &mut state);
+
+        let mut expected = Buffer::with_lines([
+            "┌Zone────────────────────┐",
+            "│Name: Zone              │",
+            "│                        │",
+            "│Upstream Zone: <none>   │",
+            "│                        │",
+            "│Downstream Zone: <none> │",
+            "│                        │",
+            "└────────────────────────┘",
+        ]);
+        expected.set_style(Rect::new(1, 3, 21, 1), Style::new().reversed());
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn test_zone_widget_highlight_style() {
+        let zone = Zone::default();
+        let highlight = Style::new().fg(Color::Black).bg(Color::Green);
+        let widget = ZoneWidget::new(&zone, &[]).highlight_style(highlight);
+        let mut state = ZoneWidgetState {
+            selected: Some(ZoneItem::Name),
+            ..Default::default()
+        };
+        let area = Rect::new(0, 0, 26, 8);
+        let mut buf = Buffer::empty(area);
+        widget.render_ref(area, &mut buf, &mut state);
+
+        assert_eq!(buf[(1, 1)].bg, Color::Green);
+        assert_eq!(buf[(1, 1)].fg, Color::Black);
+        assert!(!buf[(1, 1)].modifier.contains(Modifier::REVERSED));
+        assert_eq!(buf[(1, 3)].bg, Color::Reset);
+    }
+
+    #[test]
+    fn test_split_range() {
+        assert_eq!(
+            split_range("Zone", 1, 3),
+            ("Z".to_owned(), "on".to_owned(), "e".to_owned())
+        );
+        assert_eq!(
+            split_range("Zone", -1, 10),
+            ("".to_owned(), "Zone".to_owned(), "".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_exit_processing_restores_previous_mode() {
+        let mut home = Home::new();
+        home.update(Action::EnterInsert).unwrap();
+        home.update(Action::EnterProcessing).unwrap();
+        assert_eq!(home.mode, Mode::Processing);
+
+        home.update(Action::ExitProcessing).unwrap();
+        assert_eq!(home.mode, Mode::Insert);
+    }
+
+    #[test]
+    fn test_add_zone_links_and_navigates() {
+        let mut home = Home::new();
+        home.update(Action::AddZone).unwrap();
+        home.update(Action::AddZone).unwrap();
+        assert_eq!(home.zones.len(), 3);
+        assert_eq!(home.selected_zone, 2);
+
+        home.update(Action::PrevZone).unwrap();
+        assert_eq!(home.selected_zone, 1);
+        home.update(Action::PrevZone).unwrap();
+        home.update(Action::PrevZone).unwrap();
+        assert_eq!(home.selected_zone, 0);
+
+        home.update(Action::NextZone).unwrap();
+        assert_eq!(home.selected_zone, 1);
+    }
+
+    #[test]
+    fn test_zone_navigation_ignores_out_of_range_links() {
+        let mut home = Home::new();
+        home.zones[0].next_zone = 7;
+        home.update(Action::NextZone).unwrap();
+        home.update(Action::PrevZone).unwrap();
+        assert_eq!(home.selected_zone, 0);
+    }
+
+    #[test]
+    fn test_remove_zone_clamps_selection() {
+        let mut home = Home::new();
+        home.update(Action::AddZone).unwrap();
+        home.update(Action::RemoveSelectedZone).unwrap();
+        assert_eq!(home.zones.len(), 1);
+        assert_eq!(home.selected_zone, 0);
+
+        home.update(Action::RemoveSelectedZone).unwrap();
+        assert!(home.zones.is_empty());
+        home.update(Action::NextZone).unwrap();
+    }
+
+    fn buffer_string(terminal: &Terminal<TestBackend>) -> String {
+        let buf = terminal.backend().buffer();
+        buf.content().iter().map(|c| c.symbol()).collect()
+    }
+
+    #[test]
+    fn test_tabs_render_their_own_widget() {
+        let mut home = Home::new();
+        home.sensors.push(Sensor {
+            name: "Thermocouple".to_owned(),
+            value: 21.5,
+            unit: "C".to_owned(),
+        });
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+
+        home.update(Action::NextTab).unwrap();
+        draw(&mut home, &mut terminal);
+        assert!(buffer_string(&terminal).contains("Thermocouple: 21.50 C"));
+
+        home.update(Action::NextTab).unwrap();
+        draw(&mut home, &mut terminal);
+        assert!(buffer_string(&terminal).contains("No motors configured"));
+    }
+
+    #[test]
+    fn test_select_next_item_from_none() {
+        let mut home = Home::new();
+        for _ in 0..3 {
+            home.update(Action::SelectNextItem).unwrap();
+        }
+        assert_eq!(
+            home.zone_widget_state.selected,
+            Some(ZoneItem::DownstreamZone)
+        );
+
+        home.update(Action::SelectNextItem).unwrap();
+        assert_eq!(home.zone_widget_state.selected, Some(ZoneItem::Name));
+
+        home.update(Action::SelectPrevItem).unwrap();
+        assert_eq!(
+            home.zone_widget_state.selected,
+            Some(ZoneItem::DownstreamZone)
+        );
+    }
+
+    fn edit_zone_item(home: &mut Home, item: ZoneItem, value: &str) {
+        home.zone_widget_state.selected = Some(item);
+        home.update(Action::EditZoneItem).unwrap();
+        home.zone_widget_state.edit_input = Input::new(value.to_owned());
+        if let Some(action) = home
+            .handle_key_events(KeyEvent::from(KeyCode::Enter))
+            .unwrap()
+        {
+            home.update(action).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_zone_link_edit_rejects_non_numbers() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut home = Home::new();
+        home.register_action_handler(tx).unwrap();
+        home.zones[0].prev_zone = 0;
+
+        edit_zone_item(&mut home, ZoneItem::UpstreamZone, "abc");
+
+        assert_eq!(home.zones[0].prev_zone, 0);
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            Action::Error("Upstream zone must be a number".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_rename_zone_updates_title() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new())?;
+        harness.home.zone_widget_state.selected = Some(ZoneItem::Name);
+        harness.press_keys("<Enter><Backspace><Backspace><Backspace><Backspace>pump<Enter>")?;
+        assert_eq!(harness.home.zones[0].name, "pump");
+        let screen = harness.buffer_string()?;
+        assert!(screen.contains("┌pump"), "block title:\n{screen}");
+        assert!(!screen.contains("┌Zone"));
+
+        // A blank name keeps the old one
+        harness.press_keys("<Enter><Backspace><Backspace><Backspace><Backspace> <Enter>")?;
+        assert_eq!(harness.home.zones[0].name, "pump");
+        assert!(harness.buffer_string()?.contains("┌pump"));
+        assert_eq!(harness.home.errors, ["Zone name can't be empty"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_numeric_zone_fields_ignore_other_keys() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new())?;
+        harness.home.zone_widget_state.selected = Some(ZoneItem::DownstreamZone);
+        harness.press_keys("<Enter><Backspace><Backspace>a0x")?;
+        assert_eq!(harness.home.zone_widget_state.edit_input.value(), "0");
+        assert!(
+            !harness.home.show_recent_actions,
+            "a was bound, but only while typing"
+        );
+        harness.home.handle_paste("1a-2".to_owned())?;
+        assert_eq!(harness.home.zone_widget_state.edit_input.value(), "01-2");
+        harness.press_keys("<Esc>")?;
+
+        // The name is free text
+        harness.home.zone_widget_state.selected = Some(ZoneItem::Name);
+        harness.press_keys("<Enter>a0")?;
+        assert_eq!(harness.home.zone_widget_state.edit_input.value(), "Zonea0");
+        Ok(())
+    }
+
+    #[test]
+    fn test_zone_link_edit_empty_and_negative_clear_link() {
+        let mut home = Home::new();
+        home.zones[0].next_zone = 0;
+        edit_zone_item(&mut home, ZoneItem::DownstreamZone, "");
+        assert_eq!(home.zones[0].next_zone, -1);
+
+        home.zones[0].next_zone = 0;
+        edit_zone_item(&mut home, ZoneItem::DownstreamZone, "-7");
+        assert_eq!(home.zones[0].next_zone, -1);
+    }
+
+    #[test]
+    fn test_error_message_expires() {
+        let mut home = Home::new();
+        home.update(Action::Error("boom".to_owned())).unwrap();
+        assert!(home.message.is_some());
+        // Warnings stay twice as long as the default
+        for _ in 0..DEFAULT_MESSAGE_TICKS * 2 {
+            home.update(Action::Tick).unwrap();
+        }
+        assert!(home.message.is_none());
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("hello-ratatui-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_save_and_load_zones() -> Result<()> {
+        let path = temp_path("zones.json");
+        let mut home = Home::new();
+        home.add_zone();
+        home.zones[1].name = "Oven".to_owned();
+        home.save_zones(&path)?;
+
+        let mut loaded = Home::new();
+        loaded.load_zones(&path)?;
+        std::fs::remove_file(&path)?;
+
+        assert_eq!(loaded.zones.len(), 2);
+        assert_eq!(loaded.zones[1].name, "Oven");
+        assert_eq!(loaded.zones[1].prev_zone, 0);
+        assert_eq!(loaded.zones[0].next_zone, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_persisted_counter_round_trip() -> Result<()> {
+        let path = temp_path("persisted.json");
+        let mut home = Home::new().load_persisted(&path);
+        assert_eq!(home.counter, 0);
+        assert!(home.message.is_none(), "a missing file isn't an error");
+        home.update(Action::Increment(7))?;
+        home.update(Action::Quit)?;
+
+        let loaded = Home::new().load_persisted(&path);
+        assert_eq!(loaded.counter, 7);
+
+        // Newer files may carry fields this version doesn't know about
+        std::fs::write(&path, r#"{"version": 2, "counter": 3, "theme": "Ocean"}"#)?;
+        assert_eq!(Home::new().load_persisted(&path).counter, 3);
+
+        std::fs::write(&path, "not json")?;
+        let corrupt = Home::new().load_persisted(&path);
+        std::fs::remove_file(&path)?;
+        assert_eq!(corrupt.counter, 0);
+        assert_eq!(
+            corrupt.message.map(|(severity, ..)| severity),
+            Some(Severity::Error)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fuzzy_score_prefers_prefixes() {
+        assert_eq!(fuzzy_score("xyz", "Oven"), None);
+        assert_eq!(fuzzy_score("", "Oven"), Some(0));
+        let prefix = fuzzy_score("ov", "Oven").unwrap();
+        let scattered = fuzzy_score("ov", "Old Valve").unwrap();
+        assert!(prefix > scattered, "{prefix} <= {scattered}");
+
+        let zones = ["Old Valve", "Kiln", "Oven"].map(|name| Zone {
+            name: name.to_owned(),
+            ..Zone::default()
+        });
+        assert_eq!(zone_completions("OV", &zones), ["Oven", "Old Valve"]);
+        assert!(zone_completions("q", &zones).is_empty());
+    }
+
+    #[test]
+    fn test_tab_completes_goto() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new())?;
+        harness.home.zones[0].name = "Oven".to_owned();
+        harness.press_keys("/:goto vn")?;
+        assert_eq!(harness.home.completions(), ["Oven"]);
+        assert!(harness.buffer_string()?.contains("Tab to complete"));
+        harness.press_keys("<Tab>")?;
+        assert_eq!(harness.home.input.value(), ":goto Oven");
+
+        // Without a match there is no dropdown and Tab leaves the input alone
+        harness.press_keys("x")?;
+        assert!(harness.home.completions().is_empty());
+        assert!(!harness.buffer_string()?.contains("Tab to complete"));
+        harness.press_keys("<Tab>")?;
+        assert_eq!(harness.home.input.value(), ":goto Ovenx");
+        assert!(harness.home.selected_menu == MenuItem::Zones);
+        Ok(())
+    }
+
+    #[test]
+    fn test_seeded_zones_and_history() -> Result<()> {
+        let zones = vec![Zone::new("Preheat", -1, 1), Zone::new("Soak", 0, -1)];
+        let home = Home::new()
+            .with_zones(zones)?
+            .with_history(vec!["one".to_owned(), "two".to_owned()]);
+        let mut harness = harness::Harness::new(home)?;
+        let screen = harness.buffer_string()?;
+        assert!(screen.contains("Preheat → Soak"));
+        assert!(screen.contains("Name: Preheat"));
+        assert!(screen.contains("two"));
+        assert_eq!(harness.home.text_list, ["one", "two"]);
+
+        assert!(
+            Home::new()
+                .with_zones(vec![Zone::new("Lost", -1, 5)])
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_resize_input_pane() -> Result<()> {
+        let mut home = Home::new();
+        let original = home.body_constraints();
+        home.update(Action::GrowInput)?;
+        assert_eq!(
+            home.body_constraints(),
+            [Constraint::Percentage(55), Constraint::Percentage(45)]
+        );
+        home.update(Action::ShrinkInput)?;
+        assert_eq!(home.body_constraints(), original);
+
+        for _ in 0..20 {
+            home.update(Action::ShrinkInput)?;
+        }
+        assert_eq!(home.input_pane, 20);
+        for _ in 0..20 {
+            home.update(Action::GrowInput)?;
+        }
+        assert_eq!(home.input_pane, 80);
+
+        let path = temp_path("input-pane.json");
+        let mut home = Home::new().load_persisted(&path);
+        home.update(Action::GrowInput)?;
+        home.update(Action::Quit)?;
+        let loaded = Home::new().load_persisted(&path);
+        std::fs::remove_file(&path)?;
+        assert_eq!(loaded.input_pane, 45);
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_zones_rejects_dangling_links() -> Result<()> {
+        let path = temp_path("dangling.json");
+        std::fs::write(
+            &path,
+            r#"[{"name": "Zone", "prev_zone": -1, "next_zone": 3}]"#,
+        )?;
+
+        let mut home = Home::new();
+        home.zones[0].name = "Kept".to_owned();
+        let result = home.load_zones(&path);
+        std::fs::remove_file(&path)?;
+
+        assert!(result.is_err());
+        assert_eq!(home.zones[0].name, "Kept");
+        Ok(())
+    }
+
+    #[test]
+    fn test_counter_wraps_at_max() {
+        let mut home = Home::new().counter_max(3);
+        home.increment(3);
+        assert_eq!(home.counter, 3);
+        home.increment(1);
+        assert_eq!(home.counter, 0);
+        home.decrement(1);
+        assert_eq!(home.counter, 3);
+        home.increment(6);
+        assert_eq!(home.counter, 1);
+    }
+
+    #[test]
+    fn test_counter_saturates_without_max() {
+        let mut home = Home::new();
+        home.decrement(1);
+        assert_eq!(home.counter, 0);
+        home.counter = usize::MAX;
+        home.increment(1);
+        assert_eq!(home.counter, usize::MAX);
+    }
+
+    #[test]
+    fn test_history_is_capped() {
+        let mut home = Home::new().max_history(3);
+        for i in 0..5 {
+            home.add(format!("entry {i}"));
+        }
+        assert_eq!(home.text.len(), 3);
+        assert_eq!(home.text_list.len(), 3);
+        assert_eq!(home.text_list[0], "entry 2");
+    }
+
+    #[test]
+    fn test_scroll_history_is_clamped() {
+        let mut home = Home::new();
+        home.add("a".to_owned());
+        home.add("b".to_owned());
+        home.update(Action::ScrollHistoryUp).unwrap();
+        assert_eq!(home.text_list_state.offset(), 0);
+        for _ in 0..3 {
+            home.update(Action::ScrollHistoryDown).unwrap();
+        }
+        assert_eq!(home.text_list_state.offset(), 1);
+    }
+
+    #[test]
+    fn test_page_through_history() -> Result<()> {
+        let mut home = Home::new().list_direction(ListDirection::TopToBottom);
+        for i in 0..12 {
+            home.add(i.to_string());
+        }
+        // The list gets 5 rows inside its borders
+        home.list_area = Rect::new(0, 0, 20, 7);
+        home.update(Action::PageDownList)?;
+        assert_eq!(home.text_list_state.selected(), Some(5));
+        home.update(Action::PageDownList)?;
+        assert_eq!(home.text_list_state.selected(), Some(10));
+        home.update(Action::PageDownList)?;
+        assert_eq!(home.text_list_state.selected(), Some(11));
+
+        home.update(Action::PageUpList)?;
+        assert_eq!(home.text_list_state.selected(), Some(6));
+        home.update(Action::PageUpList)?;
+        home.update(Action::PageUpList)?;
+        assert_eq!(home.text_list_state.selected(), Some(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_page_up_follows_the_screen() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new().highlight_symbol("*"))?;
+        for i in 0..30 {
+            harness.home.add(i.to_string());
+        }
+        harness.home.text_list_state.select(Some(0));
+        // Row of the highlighted entry on screen
+        let highlighted = |harness: &mut harness::Harness| -> Result<usize> {
+            let screen = harness.buffer_string()?;
+            Ok(screen.lines().position(|line| line.contains("│*")).unwrap())
+        };
+        let oldest = highlighted(&mut harness)?;
+        let page = harness.home.list_area.height as usize - 2;
+
+        harness.press_keys("<Ctrl-b>")?;
+        assert_eq!(harness.home.text_list_state.selected(), Some(page));
+        assert!(highlighted(&mut harness)? < oldest);
+        harness.press_keys("<Ctrl-f>")?;
+        assert_eq!(highlighted(&mut harness)?, oldest);
+        Ok(())
+    }
+
+    #[test]
+    fn test_scroll_main_panel_is_clamped() -> Result<()> {
+        let mut home = Home::new();
+        for i in 0..20 {
+            home.add(format!("line {i}"));
+        }
+        let mut terminal = Terminal::new(TestBackend::new(80, 24))?;
+        home.update(Action::ScrollDown)?;
+        assert_eq!(home.scroll, 0, "nothing to scroll before the first draw");
+
+        draw(&mut home, &mut terminal);
+        assert!(home.max_scroll > 0);
+        home.update(Action::ScrollUp)?;
+        assert_eq!(home.scroll, 0);
+        for _ in 0..100 {
+            home.update(Action::ScrollDown)?;
+        }
+        assert_eq!(home.scroll, home.max_scroll);
+        draw(&mut home, &mut terminal);
+        assert!(buffer_string(&terminal).contains("line 19"));
+        assert_eq!(home.scroll, home.max_scroll);
+
+        // Shrinking the content pulls the scroll back in on the next draw
+        home.text.clear();
+        draw(&mut home, &mut terminal);
+        assert_eq!((home.scroll, home.max_scroll), (0, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_selected_entry() {
+        let mut home = Home::new();
+        for s in ["a", "b", "c"] {
+            home.add(s.to_owned());
+        }
+        home.text_list_state.select(Some(1));
+        home.update(Action::DeleteSelected).unwrap();
+        assert_eq!(home.text_list, vec!["a", "c"]);
+        assert_eq!(home.text, vec!["a", "c"]);
+        assert_eq!(home.text_list_state.selected(), Some(1));
+
+        // Deleting the last entry moves the selection up
+        home.update(Action::DeleteSelected).unwrap();
+        assert_eq!(home.text_list, vec!["a"]);
+        assert_eq!(home.text_list_state.selected(), Some(0));
+
+        home.update(Action::DeleteSelected).unwrap();
+        assert!(home.text_list.is_empty());
+        assert_eq!(home.text_list_state.selected(), None);
+
+        // Nothing left to delete
+        home.update(Action::DeleteSelected).unwrap();
+        assert!(home.text.is_empty());
+    }
+
+    #[test]
+    fn test_request_quit_without_confirm_quits() {
+        let mut home = Home::new();
+        assert_eq!(
+            home.update(Action::RequestQuit).unwrap(),
+            Some(Action::Quit)
+        );
+        assert!(!home.show_quit_dialog);
+    }
+
+    #[test]
+    fn test_quit_dialog_only_accepts_y_n_esc() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new().confirm_quit(true))?;
+        harness.press_keys("q")?;
+        assert!(harness.home.show_quit_dialog);
+
+        // Bound keys don't reach the app behind the dialog
+        harness.press_keys("j<Shift-d>/")?;
+        assert_eq!(harness.home.counter, 0);
+        assert_eq!(harness.home.mode, Mode::Normal);
+        assert!(!harness.home.show_clear_dialog);
+        assert!(harness.home.show_quit_dialog);
+
+        harness.press_keys("n")?;
+        assert!(!harness.home.show_quit_dialog);
+        assert!(!harness.actions.contains(&Action::Quit));
+
+        harness.press_keys("qy")?;
+        assert!(harness.actions.contains(&Action::Quit));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ctrl_c_and_ctrl_d_ask_before_quitting() -> Result<()> {
+        for key in ["<Ctrl-c>", "<Ctrl-d>"] {
+            let mut harness = harness::Harness::new(Home::new().confirm_quit(true))?;
+            harness.press_keys(key)?;
+            assert!(harness.home.show_quit_dialog, "{key}");
+            assert!(!harness.actions.contains(&Action::Quit), "{key}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_clear_history() -> Result<()> {
+        let mut home = Home::new();
+        for entry in ["a", "b", "c"] {
+            home.add(entry.to_owned());
+        }
+        home.text_list_state.select(Some(1));
+        assert_eq!(
+            home.update(Action::RequestClearHistory)?,
+            Some(Action::ClearHistory)
+        );
+        home.update(Action::ClearHistory)?;
+        assert!(home.text.is_empty());
+        assert!(home.text_list.is_empty());
+        assert_eq!(home.text_list_state.selected(), None);
+
+        home.update(Action::Undo)?;
+        assert_eq!(home.text_list, ["a", "b", "c"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_clear_history_can_ask_first() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new().confirm_clear_history(true))?;
+        harness.press_keys("/entry<Enter><Shift-d>")?;
+        assert!(harness.home.show_clear_dialog);
+        assert!(harness.buffer_string()?.contains("Clear history? (y/n)"));
+
+        harness.press_keys("n")?;
+        assert!(!harness.home.show_clear_dialog);
+        assert_eq!(harness.home.text_list, ["entry"]);
+
+        harness.press_keys("<Shift-d>y")?;
+        assert!(!harness.home.show_clear_dialog);
+        assert!(harness.home.text_list.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_clear_dialog_swallows_bound_keys() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new().confirm_clear_history(true))?;
+        harness.press_keys("/a<Enter>/b<Enter>")?;
+        harness.home.text_list_state.select(Some(1));
+        harness.press_keys("<Shift-d>")?;
+        assert!(harness.home.show_clear_dialog);
+
+        harness.press_keys("dj<Shift-k>")?;
+        assert_eq!(harness.home.text_list, ["a", "b"]);
+        assert_eq!(harness.home.text_list_state.selected(), Some(1));
+        assert_eq!(harness.home.counter, 0);
+        assert!(harness.home.show_clear_dialog);
+
+        harness.press_keys("<Esc>")?;
+        assert!(!harness.home.show_clear_dialog);
+        assert_eq!(harness.home.text_list, ["a", "b"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_input_cursor_counts_wide_characters() -> Result<()> {
+        let area = Rect::new(2, 1, 20, 3);
+        let input = Input::new("a你好".to_owned());
+        assert_eq!(input_cursor(&input, area, 0), Position::new(8, 2));
+        let input = input.with_cursor(2);
+        assert_eq!(input_cursor(&input, area, 0), Position::new(6, 2));
+
+        // Once the text scrolls, the cursor stays inside the box
+        let input = Input::new("你好".repeat(10));
+        let scroll = input.visual_scroll(17);
+        assert_eq!(input_cursor(&input, area, scroll), Position::new(19, 2));
+
+        let mut home = Home::new();
+        home.input = Input::new("你好".to_owned());
+        home.set_mode(Mode::Insert);
+        let mut terminal = Terminal::new(TestBackend::new(80, 24))?;
+        draw(&mut home, &mut terminal);
+        // The input box starts at column 48, and its text after the border
+        assert_eq!(terminal.get_cursor_position()?, Position::new(53, 2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_help_opens_and_toggle_flips() -> Result<()> {
+        let mut home = Home::new();
+        home.update(Action::Help)?;
+        assert!(home.show_help);
+        home.update(Action::Help)?;
+        assert!(home.show_help, "Help never closes the popup");
+
+        home.update(Action::ToggleShowHelp)?;
+        assert!(!home.show_help);
+        home.update(Action::ToggleShowHelp)?;
+        assert!(home.show_help);
+        Ok(())
+    }
+
+    #[test]
+    fn test_recall_previous_submissions() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new())?;
+        harness.press_keys("/first<Enter>/second<Enter>/<Up>")?;
+        assert_eq!(harness.home.input.value(), "second");
+        harness.press_keys("<Up>")?;
+        assert_eq!(harness.home.input.value(), "first");
+        harness.press_keys("<Up>")?;
+        assert_eq!(
+            harness.home.input.value(),
+            "first",
+            "nothing older to recall"
+        );
+        harness.press_keys("<Down>")?;
+        assert_eq!(harness.home.input.value(), "second");
+        harness.press_keys("<Down>")?;
+        assert_eq!(harness.home.input.value(), "");
+
+        // An edited entry is submitted as a new one
+        harness.press_keys("<Up><Up>!<Enter>")?;
+        assert_eq!(harness.home.text, ["first", "second", "first!"]);
+        assert_eq!(harness.home.recall, None);
+        harness.press_keys("/<Up>")?;
+        assert_eq!(harness.home.input.value(), "first!");
+        Ok(())
+    }
+
+    #[test]
+    fn test_help_popup_fits_small_terminal() {
+        let mut home = Home::new();
+        home.show_help = true;
+        let mut terminal = Terminal::new(TestBackend::new(20, 10)).unwrap();
+        draw(&mut home, &mut terminal);
+
+        let screen = buffer_string(&terminal);
+        assert!(screen.contains("Key"));
+        assert!(screen.contains("Action"));
+    }
+
+    #[test]
+    fn test_centered_rect() {
+        let area = Rect::new(0, 0, 100, 50);
+        assert_eq!(centered_rect(50, 50, area), Rect::new(25, 13, 50, 25));
+        assert_eq!(centered_area(area, 200, 10), Rect::new(0, 20, 100, 10));
+    }
+
+    #[test]
+    fn test_help_rows_follow_keymap() {
+        let home = Home::new().keymap(HashMap::from([
+            (KeyEvent::from(KeyCode::Char('x')), Action::RequestQuit),
+            (KeyEvent::from(KeyCode::Char('a')), Action::IncrementSingle),
+        ]));
+        assert_eq!(
+            home.help_rows(),
+            vec![
+                [
+                    "0-9".to_owned(),
+                    "Count for the next action, tabs are Alt-1..5".to_owned()
+                ],
+                ["a".to_owned(), "IncrementSingle".to_owned()],
+                ["x".to_owned(), "RequestQuit".to_owned()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_fills_keymap() -> Result<()> {
+        let quit = KeyEvent::from(KeyCode::Char('q'));
+        let mut home = Home::new().keymap(HashMap::from([(quit, Action::Quit)]));
+        home.register_config_handler(Config::new()?)?;
+
+        assert_eq!(home.keymap.get(&quit), Some(&Action::Quit));
+        assert_eq!(
+            home.keymap.get(&KeyEvent::from(KeyCode::Char('j'))),
+            Some(&Action::IncrementSingle)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_without_selection_does_nothing() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut home = Home::new();
+        home.register_action_handler(tx).unwrap();
+        home.add("a".to_owned());
+        home.text_list_state.select(None);
+
+        home.update(Action::CopySelected).unwrap();
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_filter_history() -> Result<()> {
+        let mut home = Home::new();
+        for entry in ["Apple", "banana", "pineapple"] {
+            home.add(entry.to_owned());
+        }
+        home.text_list_state.select(Some(2));
+
+        home.update(Action::EnterFilter)?;
+        assert_eq!(home.mode, Mode::Filter);
+        for c in "APP".chars() {
+            let action = home.handle_key_events(KeyEvent::from(KeyCode::Char(c)))?;
+            home.update(action.unwrap())?;
+        }
+        assert_eq!(home.filter, "APP");
+        assert_eq!(home.visible_history(), vec![0, 2]);
+        // "pineapple" is still visible, so it stays selected at its new position
+        assert_eq!(home.text_list_state.selected(), Some(1));
+
+        // Bound keys are typed into the filter rather than acted on
+        home.update(Action::IncrementSingle)?;
+        assert_eq!(home.counter, 0);
+
+        home.update(Action::DeleteSelected)?;
+        assert_eq!(home.mode, Mode::Filter);
+        let action = home.handle_key_events(KeyEvent::from(KeyCode::Enter))?;
+        home.update(action.unwrap())?;
+        assert_eq!(home.mode, Mode::Normal);
+        home.update(Action::DeleteSelected)?;
+        assert_eq!(home.text_list, vec!["Apple", "banana"]);
+        assert_eq!(home.text_list_state.selected(), Some(0));
+
+        home.update(Action::UpdateFilter("kiwi".to_owned()))?;
+        assert!(home.visible_history().is_empty());
+        assert_eq!(home.text_list_state.selected(), None);
+
+        home.update(Action::ClearFilter)?;
+        assert_eq!(home.visible_history(), vec![0, 1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_box_renders_above_list() -> Result<()> {
+        let mut home = Home::new();
+        home.add("alpha".to_owned());
+        home.add("beta".to_owned());
+        home.update(Action::UpdateFilter("ALP".to_owned()))?;
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 30))?;
+        draw(&mut home, &mut terminal);
+        let screen = buffer_string(&terminal);
+        assert!(screen.contains("Filter"));
+        // The main panel echoes the latest entry, so only look at the stacked list below it
+        let list = &screen[screen.find("Fight!").unwrap()..];
+        assert!(list.contains("alpha"));
+        assert!(!list.contains("beta"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo_redo() -> Result<()> {
+        let mut home = Home::new();
+        home.update(Action::IncrementSingle)?;
+        home.update(Action::AddZone)?;
+        home.update(Action::CompleteInput("hello".to_owned()))?;
+        // No-ops don't leave an undo step behind
+        home.update(Action::NextTab)?;
+        assert_eq!(home.undo_stack.len(), 3);
+
+        home.update(Action::Undo)?;
+        assert!(home.text_list.is_empty());
+        home.update(Action::Undo)?;
+        assert_eq!(home.zones.len(), 1);
+        assert_eq!(home.selected_zone, 0);
+        assert_eq!(home.counter, 1);
+
+        home.update(Action::Redo)?;
+        assert_eq!(home.zones.len(), 2);
+
+        // A new change after undoing drops what could have been redone
+        home.update(Action::IncrementSingle)?;
+        assert!(home.redo_stack.is_empty());
+        home.update(Action::Redo)?;
+        assert_eq!(home.counter, 2);
+        assert!(home.text_list.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo_delete() -> Result<()> {
+        let mut home = Home::new();
+        home.update(Action::CompleteInput("a".to_owned()))?;
+        home.update(Action::CompleteInput("b".to_owned()))?;
+        home.text_list_state.select(Some(0));
+        home.update(Action::DeleteSelected)?;
+        assert_eq!(home.text_list, vec!["b"]);
+
+        // Only the delete is rolled back, not the addition before it
+        home.update(Action::Undo)?;
+        assert_eq!(home.text_list, vec!["a", "b"]);
+        assert_eq!(home.text, vec!["a", "b"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo_zone_edit() -> Result<()> {
+        let mut home = Home::new();
+        edit_zone_item(&mut home, ZoneItem::Name, "Kiln");
+        assert_eq!(home.zones[0].name, "Kiln");
+        home.update(Action::Undo)?;
+        assert_eq!(home.zones[0].name, "Zone");
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo_depth_is_capped() -> Result<()> {
+        let mut home = Home::new();
+        for _ in 0..MAX_UNDO + 5 {
+            home.update(Action::IncrementSingle)?;
+        }
+        assert_eq!(home.undo_stack.len(), MAX_UNDO);
+        for _ in 0..MAX_UNDO + 5 {
+            home.update(Action::Undo)?;
+        }
+        assert_eq!(home.counter, 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dirty_flag() -> Result<()> {
+        let mut home = Home::new();
+        assert!(home.is_dirty());
+        let mut terminal = Terminal::new(TestBackend::new(40, 20))?;
+        draw(&mut home, &mut terminal);
+        assert!(!home.is_dirty());
+
+        // Idle ticks and frames leave the screen as it is
+        home.update(Action::Tick)?;
+        home.update(Action::Render)?;
+        assert!(!home.is_dirty());
+
+        home.update(Action::IncrementSingle)?;
+        assert!(home.is_dirty());
+        draw(&mut home, &mut terminal);
+
+        home.handle_key_events(KeyEvent::from(KeyCode::Char('x')))?;
+        assert!(home.is_dirty());
+        draw(&mut home, &mut terminal);
+        // The tick clears the echoed keys, which changes the footer
+        home.update(Action::Tick)?;
+        assert!(home.is_dirty());
+
+        // The Misc tab's tickers change with every tick and frame
+        home.selected_menu = MenuItem::Misc;
+        draw(&mut home, &mut terminal);
+        home.update(Action::Tick)?;
+        assert!(home.is_dirty());
+        draw(&mut home, &mut terminal);
+        home.update(Action::Render)?;
+        assert!(home.is_dirty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_narrow_terminal_stacks_layout() -> Result<()> {
+        let area = Rect::new(0, 0, 100, 20);
+        let mut home = Home::new();
+        assert!(!home.stacked_layout(area));
+
+        home.update(Action::Resize(40, 20))?;
+        assert!(home.stacked_layout(area));
+
+        let home = Home::new().narrow_width(30);
+        assert!(!home.stacked_layout(Rect::new(0, 0, 40, 20)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_layout_regions_do_not_overlap() -> Result<()> {
+        let mut home = Home::new();
+        home.add("entry".to_owned());
+        let mut terminal = Terminal::new(TestBackend::new(80, 24))?;
+        draw(&mut home, &mut terminal);
+        let buf = terminal.backend().buffer();
+        let row = |y: u16| -> String { (0..80).map(|x| buf[(x, y)].symbol()).collect() };
+
+        // The zone chain and widget sit above the main panel, with the input and list
+        // beside them
+        assert!(row(0).contains("Zones"));
+        assert!(row(1).starts_with("Zone "));
+        assert!(row(1).ends_with("┐"));
+        assert!(row(1).contains(" ┌Enter Input Mode"));
+        assert!(row(2).starts_with("┌Zone──"));
+        assert!(row(4).contains("│┌Fight!"));
+        assert!(row(12).starts_with("╭") && row(12).contains("ratatui async template"));
+        assert!(row(22).ends_with("╯└──────────────────────────────┘"));
+        assert!(row(23).trim_end().ends_with("[]"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_message_ticks_are_configurable() -> Result<()> {
+        let mut home = Home::new().message_ticks(5);
+        home.update(Action::Notify(Severity::Info, "boom".to_owned()))?;
+        for _ in 0..4 {
+            home.update(Action::Tick)?;
+        }
+        assert!(home.message.is_some());
+        home.update(Action::Tick)?;
+        assert!(home.message.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_footer_shows_mode_and_message() -> Result<()> {
+        let mut home = Home::new();
+        home.update(Action::EnterInsert)?;
+        home.update(Action::Error("boom".to_owned()))?;
+        let mut terminal = Terminal::new(TestBackend::new(40, 10))?;
+        draw(&mut home, &mut terminal);
+
+        let buf = terminal.backend().buffer();
+        let footer: String = (0..40).map(|x| buf[(x, 9)].symbol()).collect();
+        assert!(footer.starts_with(" INSERT  boom"));
+        assert_eq!(buf[(0, 9)].bg, Color::Yellow);
+        Ok(())
+    }
+
+    #[test]
+    fn test_numeric_prefix() -> Result<()> {
+        let mut home = Home::new();
+        for c in "12".chars() {
+            home.handle_key_events(KeyEvent::from(KeyCode::Char(c)))?;
+        }
+        assert_eq!(home.pending_count, Some(12));
+        home.update(Action::IncrementSingle)?;
+        assert_eq!(home.counter, 12);
+        assert_eq!(home.pending_count, None);
+
+        home.update(Action::IncrementSingle)?;
+        assert_eq!(home.counter, 13);
+
+        home.handle_key_events(KeyEvent::from(KeyCode::Char('0')))?;
+        assert_eq!(home.pending_count, None);
+        home.handle_key_events(KeyEvent::from(KeyCode::Char('3')))?;
+        home.handle_key_events(KeyEvent::from(KeyCode::Esc))?;
+        home.update(Action::DecrementSingle)?;
+        assert_eq!(home.counter, 12);
+        Ok(())
+    }
+
+    #[test]
+    fn test_alt_digits_select_tabs() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new())?;
+        harness.press_keys("<Alt-3>")?;
+        assert!(harness.home.selected_menu == MenuItem::Motors);
+
+        // Bare digits are only counts
+        harness.press_keys("5j")?;
+        assert!(harness.home.selected_menu == MenuItem::Motors);
+        assert_eq!(harness.home.counter, 5);
+        harness.press_keys("<Alt-1>")?;
+        assert!(harness.home.selected_menu == MenuItem::Zones);
+
+        // Out of range tabs are ignored
+        harness.home.update(Action::SelectTab(9))?;
+        assert!(harness.home.selected_menu == MenuItem::Zones);
+        harness.home.update(Action::SelectTab(0))?;
+        assert!(harness.home.selected_menu == MenuItem::Zones);
+        Ok(())
+    }
+
+    #[test]
+    fn test_harness_types_into_history() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new())?;
+        harness.press_keys("/abc<Enter>")?;
+
+        assert_eq!(harness.home.mode, Mode::Normal);
+        assert!(
+            harness
+                .actions
+                .contains(&Action::CompleteInput("abc".to_owned()))
+        );
+        let screen = harness.buffer_string()?;
+        let list = &screen[screen.find("Fight!").unwrap()..];
+        assert!(list.lines().any(|line| line.contains("│abc ")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_refresh_resets_view() -> Result<()> {
+        let mut home = Home::new();
+        home.update(Action::ToggleShowHelp)?;
+        home.update(Action::UpdateFilter("x".to_owned()))?;
+        home.update(Action::Error("boom".to_owned()))?;
+        home.update(Action::Refresh)?;
+        assert!(!home.show_help);
+        assert!(home.filter.is_empty());
+        assert!(home.message.is_none());
+
+        home.zones[0].next_zone = 7;
+        home.update(Action::Refresh)?;
+        assert!(home.message.is_some());
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rescheduling_cancels_the_pending_task() -> Result<()> {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut home = Home::new();
+        home.register_action_handler(tx)?;
+        home.update(Action::ScheduleIncrement)?;
+        tokio::task::yield_now().await;
+        home.update(Action::ScheduleIncrement)?;
+        tokio::time::sleep(Duration::from_secs(3)).await;
+
+        while let Ok(action) = rx.try_recv() {
+            home.update(action)?;
+        }
+        assert_eq!(home.counter, 1);
+        assert_eq!(home.mode, Mode::Normal);
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_coalesced_increment_completes_scheduled_task() -> Result<()> {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut home = Home::new();
+        home.register_action_handler(tx.clone())?;
+        home.update(Action::ScheduleIncrement)?;
+        // Lands right before the task's own increment, so the two get merged
+        tokio::time::sleep(DEFAULT_SCHEDULE_DELAY / 2).await;
+        tx.send(Action::Increment(2))?;
+        tokio::time::sleep(DEFAULT_SCHEDULE_DELAY * 2).await;
+
+        let actions: Vec<Action> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert!(coalesce(actions.clone()).len() < actions.len());
+        for action in coalesce(actions) {
+            home.update(action)?;
+        }
+        assert_eq!(home.counter, 3);
+        assert!(home.scheduled.is_empty());
+        assert_eq!(home.mode, Mode::Normal);
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_input_is_validated_before_it_is_added() -> Result<()> {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut home = Home::new().validate_input(|input| input != "bad");
+        home.register_action_handler(tx)?;
+        home.update(Action::CompleteInput("good".to_owned()))?;
+        home.update(Action::CompleteInput("bad".to_owned()))?;
+        assert!(home.text.is_empty(), "nothing is added until it's checked");
+
+        tokio::time::sleep(VALIDATION_DELAY * 2).await;
+        let mut actions = Vec::new();
+        while let Ok(action) = rx.try_recv() {
+            actions.push(action.clone());
+            if let Some(action) = home.update(action)? {
+                home.update(action)?;
+            }
+        }
+        assert!(actions.contains(&Action::InputValidated(true, "good".to_owned())));
+        assert_eq!(home.text, ["good"]);
+        assert!(actions.contains(&Action::Error("Rejected input: bad".to_owned())));
+        assert_eq!(home.mode, Mode::Normal);
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_quit_cancels_scheduled_tasks() -> Result<()> {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut home = Home::new();
+        home.register_action_handler(tx)?;
+        home.update(Action::ScheduleIncrement)?;
+        home.update(Action::Quit)?;
+        assert!(home.scheduled.is_empty());
+
+        tokio::time::sleep(DEFAULT_SCHEDULE_DELAY * 2).await;
+        while let Ok(action) = rx.try_recv() {
+            assert_ne!(action, Action::Increment(1));
+        }
+
+        // A task whose receiver is gone finishes quietly
+        home.update(Action::ScheduleIncrement)?;
+        drop(rx);
+        tokio::time::sleep(DEFAULT_SCHEDULE_DELAY * 2).await;
+        Ok(())
+    }
+
+    #[test]
+    fn test_timestamp_entries() -> Result<()> {
+        let mut home = Home::new()
+            .timestamp_entries(true)
+            .with_history(vec!["alpha".into(), "beta".into()]);
+        let shown = home.display_entry(0);
+        let (stamp, content) = shown.split_once(' ').unwrap();
+        assert_eq!(stamp.len(), 8);
+        assert!(stamp.split(':').all(|part| part.parse::<u8>().is_ok()));
+        assert_eq!(content, "alpha");
+        assert_eq!(home.text_list, ["alpha", "beta"]);
+
+        // The stamp is digits and colons, so it never matches a filter on its own
+        home.set_filter(":".into());
+        assert!(home.visible_history().is_empty());
+        home.set_filter("bet".into());
+        assert_eq!(home.visible_history(), [1]);
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 30))?;
+        home.set_filter(String::new());
+        draw(&mut home, &mut terminal);
+        assert!(buffer_string(&terminal).contains(&home.display_entry(1)));
+
+        let plain = Home::new().with_history(vec!["alpha".into()]);
+        assert_eq!(plain.display_entry(0), "alpha");
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_schedule_delay() -> Result<()> {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut home = Home::new().schedule_delay(Duration::from_millis(200));
+        home.register_action_handler(tx)?;
+        home.update(Action::ScheduleDecrement)?;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(rx.try_recv()?, Action::EnterProcessing);
+        home.update(Action::EnterProcessing)?;
+        assert!(rx.try_recv().is_err());
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 10))?;
+        draw(&mut home, &mut terminal);
+        assert!(buffer_string(&terminal).contains(" PROCESSING 200ms "));
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(rx.try_recv()?, Action::Decrement(1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_send_failure_is_reported() -> Result<()> {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut home = Home::new();
+        home.register_action_handler(tx)?;
+        drop(rx);
+
+        home.update(Action::EnterInsert)?;
+        type_keys(&mut home, "hi");
+        home.handle_key_events(KeyEvent::from(KeyCode::Enter))?;
+        assert_eq!(
+            home.errors,
+            vec!["Failed to send CompleteInput: channel closed"]
+        );
+        assert!(home.message.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_history_is_capped() -> Result<()> {
+        let mut home = Home::new();
+        for i in 0..MAX_ERRORS + 2 {
+            home.update(Action::Error(format!("error {i}")))?;
+        }
+        assert_eq!(home.errors.len(), MAX_ERRORS);
+        assert_eq!(home.errors[0], "error 2");
+
+        home.update(Action::ToggleErrors)?;
+        let mut terminal = Terminal::new(TestBackend::new(80, 24))?;
+        draw(&mut home, &mut terminal);
+        let screen = buffer_string(&terminal);
+        assert!(screen.contains("Errors"));
+        assert!(screen.contains(&format!("error {}", MAX_ERRORS + 1)));
+        Ok(())
+    }
+
+    fn click(home: &mut Home, column: u16, row: u16) -> Option<Action> {
+        home.handle_mouse_events(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: crossterm::event::KeyModifiers::NONE,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_mouse_selects_zone_items_and_entries() -> Result<()> {
+        let mut home = Home::new();
+        for entry in ["a", "b", "c"] {
+            home.add(entry.to_owned());
+        }
+        let mut terminal = Terminal::new(TestBackend::new(80, 24))?;
+        draw(&mut home, &mut terminal);
+
+        // The zone box starts under the chain on row 1 and its items are two rows apart
+        click(&mut home, 5, 5);
+        assert_eq!(
+            home.zone_widget_state.selected,
+            Some(ZoneItem::UpstreamZone)
+        );
+
+        // The list's bottom row holds the oldest entry
+        click(&mut home, 60, 21);
+        assert_eq!(home.text_list_state.selected(), Some(0));
+        click(&mut home, 60, 19);
+        assert_eq!(home.text_list_state.selected(), Some(2));
+
+        // Empty list rows and the main panel aren't interactive
+        assert_eq!(click(&mut home, 60, 10), None);
+        assert_eq!(click(&mut home, 5, 15), None);
+        assert_eq!(home.text_list_state.selected(), Some(2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_mouse_wheel_scrolls_list() -> Result<()> {
+        let mut home = Home::new();
+        for i in 0..30 {
+            home.add(i.to_string());
+        }
+        let mut terminal = Terminal::new(TestBackend::new(80, 24))?;
+        draw(&mut home, &mut terminal);
+        let wheel = |kind| MouseEvent {
+            kind,
+            column: 60,
+            row: 10,
+            modifiers: crossterm::event::KeyModifiers::NONE,
+        };
+
+        home.handle_mouse_events(wheel(MouseEventKind::ScrollUp))?;
+        assert_eq!(home.text_list_state.offset(), 1);
+        home.handle_mouse_events(wheel(MouseEventKind::ScrollDown))?;
+        assert_eq!(home.text_list_state.offset(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cycle_theme() -> Result<()> {
+        let mut home = Home::new();
+        home.register_config_handler(Config::new()?)?;
+        assert_eq!(home.theme().name, "Tailwind");
+        home.update(Action::CycleTheme)?;
+        assert_eq!(home.theme().name, "Ocean");
+        assert_eq!(
+            MenuItem::Zones.palette(home.theme()).c500,
+            tailwind::SKY.c500
+        );
+        home.update(Action::CycleTheme)?;
+        assert_eq!(home.theme().name, "Tailwind");
+        Ok(())
+    }
+
+    #[test]
+    fn test_cycle_text_color() -> Result<()> {
+        let mut home = Home::new();
+        assert_eq!(home.text_color, None);
+        home.update(Action::CycleTextColor)?;
+        assert_eq!(home.text_color, Some(Color::White));
+        for _ in 0..TEXT_COLORS.len() {
+            home.update(Action::CycleTextColor)?;
+        }
+        assert_eq!(home.text_color, Some(Color::White));
+
+        // Colors that aren't presets start over from the first one
+        let mut home = Home::new().text_color(Color::Red);
+        home.update(Action::CycleTextColor)?;
+        assert_eq!(home.text_color, Some(TEXT_COLORS[0]));
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 24))?;
+        draw(&mut home, &mut terminal);
+        let counter = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .find(|cell| cell.symbol() == "C" && cell.fg == Color::Cyan);
+        assert!(counter.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_tab_bar_highlights_active_tab() -> Result<()> {
+        let mut home = Home::new();
+        let mut terminal = Terminal::new(TestBackend::new(80, 24))?;
+        home.update(Action::NextTab)?;
+        draw(&mut home, &mut terminal);
+        let buf = terminal.backend().buffer();
+        let theme = Theme::default();
+
+        // The header is indented a column, and " Zones " and a divider come first
+        assert_eq!(
+            harness::buffer_to_string(buf)
+                .lines()
+                .next()
+                .map(str::trim_end),
+            Some("  Zones   Sensors   Motors   I/O   Misc.")
+        );
+        let highlight = MenuItem::Sensors.palette(&theme).c600;
+        assert_eq!(buf[(9, 0)].bg, highlight);
+        assert!(buf[(9, 0)].modifier.contains(Modifier::BOLD));
+        assert_eq!(buf[(1, 0)].bg, MenuItem::Zones.palette(&theme).c900);
+
+        // Clicking a title switches to that tab
+        click(&mut home, 22, 0);
+        assert!(home.selected_menu == MenuItem::Motors);
+        click(&mut home, 1, 0);
+        assert!(home.selected_menu == MenuItem::Zones);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tab_palettes_are_distinct() -> Result<()> {
+        let mut themes = vec![Theme::default()];
+        themes.extend(Config::new()?.themes);
+        for theme in &themes {
+            let colors: std::collections::HashSet<_> = MenuItem::iter()
+                .map(|item| item.palette(theme).c500)
+                .collect();
+            assert_eq!(colors.len(), MenuItem::iter().count(), "{}", theme.name);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_zone_widget_shows_link_names() {
+        let zones = vec![
+            Zone {
+                name: "Preheat".to_owned(),
+                next_zone: 1,
+                ..Zone::default()
+            },
+            Zone {
+                name: "Soak".to_owned(),
+                prev_zone: 0,
+                next_zone: 5,
+                ..Zone::default()
+            },
+        ];
+        let widget = ZoneWidget::new(&zones[0], &zones);
+        assert_eq!(widget.value(ZoneItem::UpstreamZone), "<none>");
+        assert_eq!(widget.value(ZoneItem::DownstreamZone), "Soak");
+        let widget = ZoneWidget::new(&zones[1], &zones);
+        assert_eq!(widget.value(ZoneItem::UpstreamZone), "Preheat");
+        assert_eq!(widget.value(ZoneItem::DownstreamZone), "<missing 5>");
+    }
+
+    #[test]
+    fn test_suspend_pauses_ticker() -> Result<()> {
+        let mut home = Home::new();
+        home.update(Action::EnterInsert)?;
+        home.update(Action::Tick)?;
+        home.update(Action::Suspend)?;
+        home.update(Action::EnterNormal)?;
+        home.update(Action::Tick)?;
+        home.update(Action::Tick)?;
+        assert_eq!(home.app_ticker, 1);
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 10))?;
+        draw(&mut home, &mut terminal);
+        assert!(buffer_string(&terminal).contains("Suspended"));
+
+        home.update(Action::Resume)?;
+        assert_eq!(home.mode, Mode::Insert);
+        home.update(Action::Tick)?;
+        assert_eq!(home.app_ticker, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_suspend_and_undo_are_bound() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new())?;
+        harness.press_keys("j<Ctrl-z>")?;
+        assert!(harness.actions.contains(&Action::Suspend));
+        assert_eq!(harness.home.suspended, Some(Mode::Normal));
+
+        harness.home.update(Action::Resume)?;
+        harness.press_keys("u")?;
+        assert_eq!(harness.home.counter, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dump_state() {
+        let mut home = Home::new();
+        home.counter = 4;
+        home.add("hello".to_owned());
+        home.text_list_state.select(Some(0));
+        home.zone_widget_state.selected = Some(ZoneItem::Name);
+
+        assert_eq!(
+            home.dump_state(),
+            serde_json::json!({
+                "counter": 4,
+                "mode": "Normal",
+                "zones": [{"name": "Zone", "prev_zone": -1, "next_zone": -1, "enabled": true}],
+                "history": ["hello"],
+                "filter": "",
+                "selected_tab": "Zones",
+                "selected_zone": 0,
+                "selected_zone_item": "Name",
+                "selected_history": 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_quit_key_can_be_rebound() -> Result<()> {
+        let x = KeyEvent::from(KeyCode::Char('x'));
+        let q = KeyEvent::from(KeyCode::Char('q'));
+        let mut home = Home::new().keymap(HashMap::from([(x, Action::RequestQuit)]));
+        assert_eq!(home.handle_key_events(x)?, Some(Action::RequestQuit));
+        assert_eq!(home.handle_key_events(q)?, None);
+
+        // Bindings only apply in Normal mode, in Insert mode the key is text
+        home.update(Action::EnterInsert)?;
+        assert_eq!(home.handle_key_events(x)?, Some(Action::Update));
+        assert_eq!(home.input.value(), "x");
+        Ok(())
+    }
+
+    #[test]
+    fn test_harness_quits_through_keymap() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new())?;
+        harness.press_keys("q")?;
+        assert!(harness.actions.contains(&Action::Quit));
+        Ok(())
+    }
+
+    #[test]
+    fn test_normal_mode_dispatches_keymap() -> Result<()> {
+        let question = KeyEvent::from(KeyCode::Char('?'));
+        let mut home = Home::new().keymap(HashMap::from([(question, Action::ToggleShowHelp)]));
+        assert_eq!(
+            home.handle_key_events(question)?,
+            Some(Action::ToggleShowHelp)
+        );
+        let shifted = KeyEvent::new(KeyCode::Char('?'), KeyModifiers::SHIFT);
+        assert_eq!(
+            home.handle_key_events(shifted)?,
+            Some(Action::ToggleShowHelp)
+        );
+        assert_eq!(
+            home.handle_key_events(KeyEvent::from(KeyCode::Char('z')))?,
+            None
+        );
+
+        // Processing mode only takes bindings, which the app would look up otherwise
+        home.update(Action::EnterProcessing)?;
+        assert_eq!(
+            home.handle_key_events(question)?,
+            Some(Action::ToggleShowHelp)
+        );
+        assert_eq!(
+            home.handle_key_events(KeyEvent::from(KeyCode::Char('z')))?,
+            None
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_gg_chord_goes_to_top() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new())?;
+        for entry in ["a", "b", "c"] {
+            harness.home.add(entry.to_owned());
+        }
+        harness.home.text_list_state.select(Some(2));
+
+        harness.press_keys("g")?;
+        assert_eq!(harness.home.text_list_state.selected(), Some(2));
+        harness.press_keys("g")?;
+        assert_eq!(harness.home.text_list_state.selected(), Some(0));
+        assert!(harness.home.pending_keys.is_empty());
+
+        harness.press_keys("<Shift-g>")?;
+        assert_eq!(harness.home.text_list_state.selected(), Some(2));
+
+        // A key that doesn't continue the chord still does its own thing
+        harness.press_keys("gj")?;
+        assert_eq!(harness.home.counter, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_partial_chord_expires() -> Result<()> {
+        let g = KeyEvent::from(KeyCode::Char('g'));
+        let mut home = Home::new()
+            .chords(HashMap::from([(vec![g, g], Action::GotoTop)]))
+            .chord_ticks(2);
+        assert_eq!(home.handle_key_events(g)?, Some(Action::Update));
+        home.update(Action::Tick)?;
+        assert_eq!(home.pending_keys, vec![g]);
+        home.update(Action::Tick)?;
+        assert!(home.pending_keys.is_empty());
+        assert_eq!(home.handle_key_events(g)?, Some(Action::Update));
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_input_is_rejected() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new())?;
+        harness.press_keys("/ <Enter>")?;
+        assert!(harness.home.text_list.is_empty());
+        assert!(harness.actions.contains(&Action::EmptyInput));
+        assert!(harness.home.message.is_some());
+
+        let mut harness = harness::Harness::new(Home::new().allow_empty_input(true))?;
+        harness.press_keys("/<Enter>")?;
+        assert_eq!(harness.home.text_list, vec![""]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_input_clears_after_submit() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new())?;
+        harness.press_keys("/hello<Enter>/")?;
+        assert_eq!(harness.home.mode, Mode::Insert);
+        assert_eq!(harness.home.input.value(), "");
+        assert_eq!(harness.home.input.cursor(), 0);
+        assert_eq!(harness.home.input.visual_scroll(3), 0);
+        assert_eq!(harness.home.text_list, vec!["hello"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_toggle_wrap() -> Result<()> {
+        let mut home = Home::new();
+        home.add("the quick brown fox jumps over the lazy dog".to_owned());
+        let mut terminal = Terminal::new(TestBackend::new(40, 70))?;
+        draw(&mut home, &mut terminal);
+        assert!(!buffer_string(&terminal).contains("lazy"));
+
+        home.update(Action::ToggleWrap)?;
+        draw(&mut home, &mut terminal);
+        assert!(buffer_string(&terminal).contains("lazy dog"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_paste_into_input() -> Result<()> {
+        let mut home = Home::new();
+        assert_eq!(home.handle_paste("ignored".to_owned())?, None);
+
+        home.update(Action::EnterInsert)?;
+        type_keys(&mut home, ">");
+        home.handle_paste("a b c".to_owned())?;
+        assert_eq!(home.input.value(), ">a b c");
+        home.handle_paste("\nd\r\ne".to_owned())?;
+        assert_eq!(home.input.value(), ">a b c d e");
+        Ok(())
+    }
+
+    fn header_row(terminal: &Terminal<TestBackend>) -> String {
+        let buf = terminal.backend().buffer();
+        (0..buf.area.width).map(|x| buf[(x, 0)].symbol()).collect()
+    }
+
+    #[test]
+    fn test_counter_gauge_in_header() -> Result<()> {
+        let mut home = Home::new().counter_max(4);
+        let mut terminal = Terminal::new(TestBackend::new(80, 24))?;
+        home.update(Action::Increment(2))?;
+        draw(&mut home, &mut terminal);
+        let header = header_row(&terminal);
+        assert!(header.contains("2/4"), "{header}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_counter_sparkline_without_max() -> Result<()> {
+        let mut home = Home::new();
+        let mut terminal = Terminal::new(TestBackend::new(80, 24))?;
+        draw(&mut home, &mut terminal);
+        assert_eq!(header_row(&terminal)[59..].trim(), "");
+
+        for _ in 0..3 {
+            home.increment(1);
+        }
+        home.decrement(1);
+        assert_eq!(home.counter_trend, [1, 2, 3, 2]);
+        draw(&mut home, &mut terminal);
+        let trend: String = header_row(&terminal).chars().skip(59).take(4).collect();
+        assert_eq!(trend, "▂▅█▅");
+        Ok(())
+    }
+
+    #[test]
+    fn test_action_counts_overlay() -> Result<()> {
+        let mut home = Home::new();
+        home.update(Action::Tick)?;
+        home.update(Action::Tick)?;
+        home.update(Action::Increment(1))?;
+        home.update(Action::Increment(2))?;
+        home.update(Action::ToggleActionCounts)?;
+        assert_eq!(
+            home.count_rows(),
+            [
+                ["2".to_owned(), "Increment".to_owned()],
+                ["2".to_owned(), "Tick".to_owned()],
+                ["1".to_owned(), "ToggleActionCounts".to_owned()],
+            ]
+        );
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 24))?;
+        draw(&mut home, &mut terminal);
+        let screen = buffer_string(&terminal);
+        assert!(screen.contains("Action Counts"));
+        assert!(screen.contains("1     ToggleActionCounts"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_tick_rate_is_measured_and_settable() -> Result<()> {
+        let mut home = Home::new().tick_rate(Duration::from_millis(250));
+        let now = Instant::now();
+        assert_eq!(home.measured_tick_rate(now), None);
+
+        home.ticks_since = Some((now - Duration::from_secs(2), 9));
+        assert_eq!(home.measured_tick_rate(now), Some(4.0));
+
+        home.update(Action::SetTickRate(Duration::ZERO))?;
+        assert_eq!(home.tick_rate, Duration::from_millis(250));
+        home.update(Action::SetTickRate(Duration::from_millis(100)))?;
+        assert_eq!(home.tick_rate, Duration::from_millis(100));
+        assert_eq!(home.ticks_since, None);
+
+        home.tick();
+        assert_eq!(home.ticks_since.map(|(_, ticks)| ticks), Some(1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_zone_widget_compact() {
+        let zones = [
+            Zone {
+                next_zone: 1,
+                ..Zone::default()
+            },
+            Zone {
+                name: "Kiln".to_owned(),
+                prev_zone: 0,
+                ..Zone::default()
+            },
+        ];
+        let mut state = ZoneWidgetState::default();
+        state.set_compact(true);
+        let area = Rect::new(0, 0, 24, 2);
+        let mut buf = Buffer::empty(area);
+        let [first, second] = Layout::vertical([Constraint::Length(1); 2]).areas(area);
+        ZoneWidget::new(&zones[0], &zones).render_ref(first, &mut buf, &mut state);
+        ZoneWidget::new(&zones[1], &zones).render_ref(second, &mut buf, &mut state);
+
+        let expected = Buffer::with_lines(["Zone [<none>→Kiln]      ", "Kiln [Zone→<none>]      "]);
+        assert_eq!(buf, expected);
+        assert!(state.item_areas.is_empty());
+    }
+
+    #[test]
+    fn test_short_zones_tab_draws_compact_zone() -> Result<()> {
+        let mut home = Home::new();
+        let mut terminal = Terminal::new(TestBackend::new(80, 12))?;
+        draw(&mut home, &mut terminal);
+        assert!(home.zone_widget_state.compact);
+        let buf = terminal.backend().buffer();
+        let row: String = (0..20).map(|x| buf[(x, 2)].symbol()).collect();
+        assert_eq!(row, "Zone [<none>→<none>]");
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 40))?;
+        draw(&mut home, &mut terminal);
+        assert!(!home.zone_widget_state.compact);
+        Ok(())
+    }
+
+    #[test]
+    fn test_zone_widget_renders_every_item() {
+        let zone = Zone::default();
+        let mut state = ZoneWidgetState::default();
+        let area = Rect::new(0, 0, 30, 10);
+        let mut buf = Buffer::empty(area);
+        ZoneWidget::new(&zone, &[]).render_ref(area, &mut buf, &mut state);
+
+        let items: Vec<_> = state.item_areas.iter().map(|(item, _)| *item).collect();
+        assert_eq!(items, ZoneItem::iter().collect::<Vec<_>>());
+        assert_eq!(items.len(), 3);
+        for (item, item_area) in &state.item_areas {
+            assert!(!item_area.is_empty(), "{item} has no row");
+        }
+    }
+
+    #[test]
+    fn test_zone_widget_item_at() {
+        let zone = Zone::default();
+        let widget = ZoneWidget::new(&zone, &[]);
+        let area = Rect::new(2, 1, 26, 8);
+        let item_at = |x, y| widget.item_at(area, Position::new(x, y));
+
+        assert_eq!(item_at(3, 2), Some(ZoneItem::Name));
+        assert_eq!(item_at(10, 3), Some(ZoneItem::Name));
+        assert_eq!(item_at(10, 4), Some(ZoneItem::UpstreamZone));
+        assert_eq!(item_at(26, 6), Some(ZoneItem::DownstreamZone));
+
+        // The border and anything outside the widget aren't items
+        assert_eq!(item_at(2, 2), None);
+        assert_eq!(item_at(27, 4), None);
+        assert_eq!(item_at(10, 1), None);
+        assert_eq!(item_at(10, 8), None);
+        assert_eq!(item_at(0, 0), None);
+        assert_eq!(item_at(10, 20), None);
+    }
+
+    #[test]
+    fn test_message_severity_sets_color_and_duration() -> Result<()> {
+        let mut home = Home::new().message_ticks(1);
+        let mut terminal = Terminal::new(TestBackend::new(40, 10))?;
+        let cases = [
+            (Severity::Info, Color::Cyan, 1),
+            (Severity::Warn, Color::Yellow, 2),
+            (Severity::Error, Color::Red, 3),
+        ];
+        for (severity, color, ticks) in cases {
+            home.update(Action::Notify(severity, "note".to_owned()))?;
+            draw(&mut home, &mut terminal);
+            assert_eq!(terminal.backend().buffer()[(9, 9)].fg, color);
+            for _ in 1..ticks {
+                home.update(Action::Tick)?;
+            }
+            assert!(home.message.is_some(), "{severity} expired early");
+            home.update(Action::Tick)?;
+            assert!(home.message.is_none(), "{severity} stayed too long");
+        }
+        // Only warnings and errors are worth keeping around
+        assert_eq!(home.errors.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_schedule_without_sender_does_not_panic() -> Result<()> {
+        let mut home = Home::new();
+        home.schedule_increment(1);
+        home.update(Action::ScheduleDecrement)?;
+        assert!(home.scheduled.is_empty());
+        assert_eq!(home.counter, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_modal_clears_and_fits_title() -> Result<()> {
+        let mut terminal = Terminal::new(TestBackend::new(20, 5))?;
+        terminal.draw(|f| {
+            f.render_widget(
+                Paragraph::new("x".repeat(100)).wrap(Wrap::default()),
+                f.area(),
+            );
+            render_modal(
+                f,
+                f.area(),
+                "Title".into(),
+                Style::default(),
+                Size::new(2, 1),
+                Paragraph::new("ok"),
+            );
+        })?;
+        let expected = Buffer::with_lines([
+            "xxxxxxxxxxxxxxxxxxxx",
+            "xxxxxxxxxxxxxxxxxxxx",
+            "xxxxxxx┌Title┐xxxxxx",
+            "xxxxxxx│ok   │xxxxxx",
+            "xxxxxxx└─────┘xxxxxx",
+        ]);
+        terminal.backend().assert_buffer(&expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_insert_edits_the_selected_zone_item() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new())?;
+        harness.press_keys("<Down>/x<Esc>")?;
+        assert_eq!(harness.home.mode, Mode::Normal);
+        assert_eq!(harness.home.zone_widget_state.editing(), None);
+        assert_eq!(harness.home.zones[0].name, "Zone");
+        assert_eq!(harness.home.input.value(), "");
+
+        harness.press_keys("/x<Enter>")?;
+        assert_eq!(harness.home.zones[0].name, "Zonex");
+        assert!(harness.home.text_list.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_insert_edits_the_input_without_a_zone_item() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new())?;
+        harness.press_keys("<Down><Esc><Enter>x<Esc>")?;
+        assert_eq!(harness.home.mode, Mode::Normal);
+        assert_eq!(harness.home.zone_widget_state.selected, None);
+        // Esc discards what was typed
+        assert_eq!(harness.home.input.value(), "");
+
+        harness.press_keys("/y<Enter>")?;
+        assert_eq!(harness.home.zones[0].name, "Zone");
+        assert_eq!(harness.home.text_list, ["y"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_commit_on_escape() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new().commit_on_escape(true))?;
+        harness.press_keys("/kept<Esc>")?;
+        assert_eq!(harness.home.mode, Mode::Normal);
+        assert_eq!(harness.home.input.value(), "");
+        assert_eq!(harness.home.text_list, ["kept"]);
+
+        // A blank input is dropped rather than reported as empty
+        harness.press_keys("/ <Esc>")?;
+        assert_eq!(harness.home.text_list, ["kept"]);
+        assert!(harness.home.errors.is_empty());
+
+        // Zone fields are still discarded
+        harness.press_keys("<Down>/x<Esc>")?;
+        assert_eq!(harness.home.zones[0].name, "Zone");
+        assert_eq!(harness.home.text_list, ["kept"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_recent_actions_are_recorded() -> Result<()> {
+        let mut home = Home::new();
+        home.update(Action::Tick)?;
+        home.update(Action::Increment(1))?;
+        home.update(Action::Update)?;
+        assert_eq!(
+            home.recent_actions(),
+            [Action::Tick, Action::Increment(1), Action::Update]
+        );
+
+        for _ in 0..MAX_RECENT_ACTIONS {
+            home.update(Action::Render)?;
+        }
+        assert_eq!(home.recent_actions().len(), MAX_RECENT_ACTIONS);
+        assert!(!home.recent_actions().contains(&Action::Update));
+        Ok(())
+    }
+
+    #[test]
+    fn test_frame_times_are_recorded() -> Result<()> {
+        let mut home = Home::new();
+        // The first tick only starts the clock
+        home.update(Action::Render)?;
+        assert!(home.frame_times.is_empty());
+        home.update(Action::Render)?;
+        home.update(Action::Render)?;
+        assert_eq!(home.frame_times.len(), 2);
+
+        for _ in 0..FRAME_TIMES_LEN * 2 {
+            home.update(Action::Render)?;
+        }
+        assert_eq!(home.frame_times.len(), FRAME_TIMES_LEN);
+
+        home.update(Action::ToggleFrameTimes)?;
+        let mut terminal = Terminal::new(TestBackend::new(100, 30))?;
+        draw(&mut home, &mut terminal);
+        assert!(buffer_string(&terminal).contains("Frame Times"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_narrow_frame_times_keep_the_newest() -> Result<()> {
+        let mut home = Home::new();
+        home.frame_times = (0..FRAME_TIMES_LEN)
+            .map(|_| Duration::from_millis(1))
+            .collect();
+        *home.frame_times.back_mut().unwrap() = Duration::from_millis(100);
+        home.update(Action::ToggleFrameTimes)?;
+        let mut terminal = Terminal::new(TestBackend::new(60, 24))?;
+        draw(&mut home, &mut terminal);
+
+        // The slow last frame is the full bar at the right of the popup's top row
+        let screen = harness::buffer_to_string(terminal.backend().buffer());
+        let lines: Vec<&str> = screen.lines().collect();
+        let title = lines
+            .iter()
+            .position(|line| line.contains("Frame Times"))
+            .unwrap();
+        assert!(lines[title + 1].contains("█│"), "{screen}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_direction_and_highlight_are_configurable() -> Result<()> {
+        let mut home = Home::new()
+            .list_direction(ListDirection::TopToBottom)
+            .highlight_symbol("* ")
+            .highlight_color(Color::Magenta);
+        home.add("first".to_owned());
+        home.add("second".to_owned());
+        home.text_list_state.select(Some(1));
+        let mut terminal = Terminal::new(TestBackend::new(80, 24))?;
+        draw(&mut home, &mut terminal);
+
+        // The history box sits under the 3 row input box, right of the 60% content
+        let buf = terminal.backend().buffer();
+        let row = |y: u16| (48..80).map(|x| buf[(x, y)].symbol()).collect::<String>();
+        assert_eq!(row(4), "┌Fight!────────────────────────┐");
+        assert_eq!(row(5), "│  first                       │");
+        assert_eq!(row(6), "│* second                      │");
+        assert_eq!(buf[(50, 6)].fg, Color::Magenta);
+
+        assert_eq!(click(&mut home, 52, 5), Some(Action::Update));
+        assert_eq!(home.text_list_state.selected(), Some(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unfocused_list_mutes_highlight() -> Result<()> {
+        let mut home = Home::new().list_direction(ListDirection::TopToBottom);
+        home.add("first".to_owned());
+        home.text_list_state.select(Some(0));
+        let mut terminal = Terminal::new(TestBackend::new(80, 24))?;
+        draw(&mut home, &mut terminal);
+        assert_eq!(terminal.backend().buffer()[(52, 5)].fg, Color::Blue);
+
+        home.update(Action::CycleFocus)?;
+        assert_eq!(home.focus, Focus::Zones);
+        draw(&mut home, &mut terminal);
+        assert_eq!(terminal.backend().buffer()[(52, 5)].fg, Color::DarkGray);
+        home.update(Action::CycleFocus)?;
+        assert_eq!(home.focus, Focus::Input);
+        home.update(Action::CycleFocus)?;
+        assert_eq!(home.focus, Focus::List);
+
+        // Clicking an entry gives the list focus back
+        home.update(Action::CycleFocus)?;
+        draw(&mut home, &mut terminal);
+        assert_eq!(click(&mut home, 52, 5), Some(Action::Update));
+        assert_eq!(home.focus, Focus::List);
+        Ok(())
+    }
+
+    #[test]
+    fn test_focus_follows_typing() -> Result<()> {
+        let mut harness =
+            harness::Harness::new(Home::new().list_direction(ListDirection::TopToBottom))?;
+        harness.press_keys("/first<Enter>")?;
+        harness.home.text_list_state.select(Some(0));
+        harness.press_keys("/")?;
+        assert_eq!(harness.home.focus, Focus::Input);
+        // The entry's first letter, in the history box right of the content
+        let entry_color = |harness: &mut harness::Harness| -> Result<Color> {
+            harness.buffer_string()?;
+            let buf = harness.terminal.backend().buffer();
+            let x = (48..80).find(|&x| buf[(x, 5)].symbol() == "f").unwrap();
+            Ok(buf[(x, 5)].fg)
         };
-        Ok(Some(action))
+        assert_eq!(entry_color(&mut harness)?, Color::DarkGray);
+
+        harness.press_keys("<Esc>")?;
+        assert_eq!(harness.home.focus, Focus::List);
+        assert_eq!(entry_color(&mut harness)?, Color::Blue);
+
+        // Selecting and editing a zone item is typing into the zones
+        harness.press_keys("<Down>")?;
+        assert_eq!(harness.home.focus, Focus::Zones);
+        harness.home.focus = Focus::List;
+        harness.press_keys("<Enter>")?;
+        assert_eq!(harness.home.focus, Focus::Zones);
+        harness.press_keys("<Esc>")?;
+        assert_eq!(harness.home.focus, Focus::List);
+        Ok(())
     }
 
-    fn update(&mut self, action: Action) -> Result<Option<Action>> {
-        match action {
-            Action::Tick => self.tick(),
-            Action::Render => self.render_tick(),
-            Action::ToggleShowHelp => self.show_help = !self.show_help,
-            Action::IncrementSingle if self.mode != Mode::Insert => self.increment(1),
-            Action::DecrementSingle if self.mode != Mode::Insert => self.decrement(1),
-            Action::ScheduleIncrement if self.mode != Mode::Insert => self.schedule_increment(1),
-            Action::ScheduleDecrement if self.mode != Mode::Insert => self.schedule_decrement(1),
-            Action::Increment(i) => self.increment(i),
-            Action::Decrement(i) => self.decrement(i),
-            Action::CompleteInput(s) => self.add(s),
-            Action::EnterNormal => {
-                self.mode = Mode::Normal;
+    #[test]
+    fn test_list_keys_take_focus_back() -> Result<()> {
+        let mut harness =
+            harness::Harness::new(Home::new().list_direction(ListDirection::TopToBottom))?;
+        harness.press_keys("/first<Enter>/second<Enter>")?;
+        for keys in [
+            "<Shift-Up>",
+            "<Shift-Down>",
+            "<Ctrl-b>",
+            "<Ctrl-f>",
+            "<Shift-k>",
+            "<Shift-j>",
+            "d",
+        ] {
+            harness.press_keys("<Down>")?;
+            assert_eq!(harness.home.focus, Focus::Zones);
+            harness.press_keys(keys)?;
+            assert_eq!(harness.home.focus, Focus::List, "{keys}");
+        }
+        // The one entry left is highlighted as the one the list keys act on
+        harness.buffer_string()?;
+        let buf = harness.terminal.backend().buffer();
+        let x = (48..80).find(|&x| buf[(x, 5)].symbol() == "f").unwrap();
+        assert_eq!(buf[(x, 5)].fg, Color::Blue);
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_before_shift_g_jumps_to_entry() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new())?;
+        for entry in ["a", "b", "c", "d", "e"] {
+            harness.home.add(entry.to_owned());
+        }
+        harness.press_keys("3<Shift-g>")?;
+        assert_eq!(harness.home.text_list_state.selected(), Some(3));
+        assert_eq!(harness.home.pending_count, None);
+
+        harness.press_keys("gg")?;
+        assert_eq!(harness.home.text_list_state.selected(), Some(0));
+        harness.press_keys("<Shift-g>")?;
+        assert_eq!(harness.home.text_list_state.selected(), Some(4));
+
+        harness.press_keys("gg99<Shift-g>")?;
+        assert_eq!(harness.home.text_list_state.selected(), Some(4));
+        Ok(())
+    }
+
+    #[test]
+    fn test_remove_zone_repairs_links() -> Result<()> {
+        let mut home = Home::new();
+        home.update(Action::AddZone)?;
+        home.update(Action::AddZone)?;
+        home.update(Action::AddZone)?;
+        // Zone -> Zone 1 -> Zone 2 -> Zone 3, with Zone 1 also looping back to Zone 3
+        home.zones[1].prev_zone = 3;
+        home.selected_zone = 3;
+
+        home.update(Action::RemoveZone(2))?;
+        let links: Vec<_> = home
+            .zones
+            .iter()
+            .map(|zone| (zone.name.as_str(), zone.prev_zone, zone.next_zone))
+            .collect();
+        assert_eq!(
+            links,
+            [("Zone", -1, 1), ("Zone 1", 2, -1), ("Zone 3", -1, -1)]
+        );
+        assert_eq!(home.selected_zone, 2);
+        assert!(validate_links(&home.zones).is_ok());
+
+        home.update(Action::Undo)?;
+        assert_eq!(home.zones.len(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_insert_zone_after_splices_links() -> Result<()> {
+        let mut home = Home::new();
+        home.update(Action::AddZone)?;
+        home.update(Action::InsertZoneAfter(0))?;
+        assert_eq!(home.selected_zone, 2);
+        let links: Vec<_> = home
+            .zones
+            .iter()
+            .map(|zone| (zone.prev_zone, zone.next_zone))
+            .collect();
+        assert_eq!(links, [(-1, 2), (2, -1), (0, 1)]);
+
+        // At the end of the chain there is no next zone to link up with
+        home.update(Action::InsertZoneAfter(1))?;
+        assert_eq!(home.zones[1].next_zone, 3);
+        assert_eq!((home.zones[3].prev_zone, home.zones[3].next_zone), (1, -1));
+        assert!(validate_links(&home.zones).is_ok());
+
+        home.update(Action::InsertZoneAfter(9))?;
+        assert_eq!(home.zones.len(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_mode_keeps_dependent_state() -> Result<()> {
+        let mut home = Home::new();
+        home.update(Action::EnterInsert)?;
+        type_keys(&mut home, "abc");
+        home.handle_key_events(KeyEvent::from(KeyCode::Left))?;
+        assert_eq!(home.input.cursor(), 2);
+
+        home.set_mode(Mode::Processing);
+        assert_eq!(home.mode(), Mode::Processing);
+        assert_eq!(home.prev_mode, Mode::Insert);
+        assert_eq!(home.input.cursor(), 3);
+        assert_eq!(home.input.value(), "abc");
+
+        home.set_mode(Mode::Processing);
+        assert_eq!(home.prev_mode, Mode::Insert);
+        home.update(Action::ExitProcessing)?;
+        assert_eq!(home.mode(), Mode::Insert);
+        Ok(())
+    }
+
+    #[test]
+    fn test_border_config_applies_everywhere() -> Result<()> {
+        let mut home = Home::new().borders(BorderConfig::all(BorderType::Double));
+        let mut terminal = Terminal::new(TestBackend::new(80, 24))?;
+        draw(&mut home, &mut terminal);
+        let screen = buffer_string(&terminal);
+        // The zone widget, the main panel, the input box and the history list
+        assert_eq!(screen.matches('╔').count(), 4);
+        assert!(!screen.contains('┌') && !screen.contains('╭'));
+
+        home.update(Action::NextTab)?;
+        draw(&mut home, &mut terminal);
+        assert_eq!(terminal.backend().buffer()[(0, 1)].symbol(), "╔");
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_selected_entry() -> Result<()> {
+        let mut harness =
+            harness::Harness::new(Home::new().list_direction(ListDirection::TopToBottom))?;
+        for entry in ["a", "b", "c"] {
+            harness.home.add(entry.to_owned());
+        }
+        harness.home.text_list_state.select(Some(1));
+        harness.press_keys("<Shift-k>")?;
+        assert_eq!(harness.home.text_list, ["b", "a", "c"]);
+        assert_eq!(harness.home.text, ["b", "a", "c"]);
+        assert_eq!(harness.home.text_list_state.selected(), Some(0));
+
+        // The first entry can't move any further
+        harness.press_keys("<Shift-k>")?;
+        assert_eq!(harness.home.text_list, ["b", "a", "c"]);
+
+        harness.press_keys("<Shift-j><Shift-j><Shift-j>")?;
+        assert_eq!(harness.home.text_list, ["a", "c", "b"]);
+        assert_eq!(harness.home.text_list_state.selected(), Some(2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_selected_entry_follows_the_screen() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new())?;
+        for entry in ["a", "b", "c"] {
+            harness.home.add(entry.to_owned());
+        }
+        harness.home.text_list_state.select(Some(1));
+        // Entries in the history box, from the top of the screen down
+        let rows = |harness: &mut harness::Harness| -> Result<Vec<String>> {
+            harness.buffer_string()?;
+            let buf = harness.terminal.backend().buffer();
+            Ok((0..24)
+                .map(|y| (49..79).map(|x| buf[(x, y)].symbol()).collect::<String>())
+                .map(|row| row.trim_start_matches(['>', ' ']).trim().to_owned())
+                .filter(|row| ["a", "b", "c"].contains(&row.as_str()))
+                .collect())
+        };
+        assert_eq!(rows(&mut harness)?, ["c", "b", "a"]);
+
+        harness.press_keys("<Shift-k>")?;
+        assert_eq!(rows(&mut harness)?, ["b", "c", "a"]);
+        assert_eq!(harness.home.selected_entry(), Some(2));
+        harness.press_keys("<Shift-j><Shift-j>")?;
+        assert_eq!(rows(&mut harness)?, ["c", "a", "b"]);
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_record_and_replay_actions() -> Result<()> {
+        let path = temp_path("recording.jsonl");
+        let mut home = Home::new();
+        home.register_config_handler(Config::new()?)?;
+        let key = |c| Some(Event::Key(KeyEvent::from(KeyCode::Char(c))));
+        home.update(Action::StartRecording(path.clone()))?;
+        assert_eq!(home.handle_events(key('j'))?, Some(Action::IncrementSingle));
+        tokio::time::advance(Duration::from_millis(500)).await;
+        home.update(Action::Tick)?;
+        home.update(Action::Increment(2))?;
+        home.handle_events(key('k'))?;
+        home.update(Action::StopRecording)?;
+        assert_eq!(std::fs::read_to_string(&path)?.lines().count(), 2);
+
+        assert!(home.replay(&path).is_err());
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        home.register_action_handler(tx)?;
+        home.replay(&path)?;
+        assert_eq!(rx.recv().await, Some(Action::IncrementSingle));
+        let first = tokio::time::Instant::now();
+        assert_eq!(rx.recv().await, Some(Action::DecrementSingle));
+        assert_eq!(first.elapsed(), Duration::from_millis(500));
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_replay_does_not_repeat_derived_actions() -> Result<()> {
+        async fn settle(
+            home: &mut Home,
+            rx: &mut tokio::sync::mpsc::UnboundedReceiver<Action>,
+        ) -> Result<()> {
+            for _ in 0..3 {
+                tokio::time::sleep(DEFAULT_SCHEDULE_DELAY).await;
+                while let Ok(action) = rx.try_recv() {
+                    if let Some(action) = home.update(action)? {
+                        home.update(action)?;
+                    }
+                }
             }
-            Action::EnterInsert => {
-                self.mode = Mode::Insert;
+            Ok(())
+        }
+
+        let path = temp_path("derived.jsonl");
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut home = Home::new();
+        home.register_action_handler(tx)?;
+        home.register_config_handler(Config::new()?)?;
+        home.update(Action::StartRecording(path.clone()))?;
+        let press = |home: &mut Home, key: KeyEvent| -> Result<()> {
+            if let Some(action) = home.handle_events(Some(Event::Key(key)))? {
+                home.update(action)?;
             }
-            Action::EnterProcessing => {
-                self.mode = Mode::Processing;
+            Ok(())
+        };
+        press(
+            &mut home,
+            KeyEvent::new(KeyCode::Char('j'), KeyModifiers::ALT),
+        )?;
+        // Enter sends the input from the key handler, which then sets the counter
+        for c in "/:counter 5".chars() {
+            press(&mut home, KeyEvent::from(KeyCode::Char(c)))?;
+        }
+        press(&mut home, KeyEvent::from(KeyCode::Enter))?;
+        settle(&mut home, &mut rx).await?;
+        assert_eq!(home.counter, 6);
+        home.update(Action::StopRecording)?;
+
+        let recorded = std::fs::read_to_string(&path)?;
+        assert_eq!(recorded.lines().count(), 4, "{recorded}");
+        home.counter = 0;
+        home.replay(&path)?;
+        settle(&mut home, &mut rx).await?;
+        assert_eq!(home.counter, 6);
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_record_and_replay_commands() -> Result<()> {
+        assert_eq!(
+            parse_command("record out.jsonl"),
+            Ok(Action::StartRecording("out.jsonl".into()))
+        );
+        assert_eq!(parse_command("stop"), Ok(Action::StopRecording));
+
+        /// Runs `action` and whatever it sends to the app, until nothing is left.
+        fn run(
+            home: &mut Home,
+            rx: &mut tokio::sync::mpsc::UnboundedReceiver<Action>,
+            mut next: Option<Action>,
+        ) -> Result<()> {
+            loop {
+                while let Some(action) = next {
+                    next = home.update(action)?;
+                }
+                match rx.try_recv() {
+                    Ok(action) => next = Some(action),
+                    Err(_) => return Ok(()),
+                }
             }
-            Action::ExitProcessing => {
-                // TODO: Make this go to previous mode instead
-                self.mode = Mode::Normal;
+        }
+        fn press(
+            home: &mut Home,
+            rx: &mut tokio::sync::mpsc::UnboundedReceiver<Action>,
+            keys: &str,
+        ) -> Result<()> {
+            for c in keys.chars() {
+                let key = match c {
+                    '\n' => KeyEvent::from(KeyCode::Enter),
+                    c => KeyEvent::from(KeyCode::Char(c)),
+                };
+                let action = home.handle_events(Some(Event::Key(key)))?;
+                run(home, rx, action)?;
             }
-            _ => (),
+            Ok(())
         }
-        Ok(None)
+
+        let path = temp_path("commands.jsonl");
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut home = Home::new();
+        home.register_action_handler(tx)?;
+        home.register_config_handler(Config::new()?)?;
+        let record = Action::CompleteInput(format!(":record {}", path.display()));
+        run(&mut home, &mut rx, Some(record))?;
+        press(&mut home, &mut rx, "jj")?;
+        // Opening the input is recorded, but not the command to stop
+        press(&mut home, &mut rx, "/:stop\n")?;
+        assert_eq!(home.counter, 2);
+        let recorded = std::fs::read_to_string(&path)?;
+        assert_eq!(recorded.lines().count(), 4, "{recorded}");
+        assert!(!recorded.contains("stop"), "{recorded}");
+
+        home.counter = 0;
+        let replay = Action::CompleteInput(format!(":replay {}", path.display()));
+        run(&mut home, &mut rx, Some(replay))?;
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        run(&mut home, &mut rx, None)?;
+        assert_eq!(home.counter, 2);
+        std::fs::remove_file(path)?;
+        Ok(())
     }
 
-    fn draw(&mut self, f: &mut Frame<'_>, rect: Rect) -> Result<()> {
-        let rects = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(100), Constraint::Min(3)].as_ref())
-            .split(rect);
+    #[test]
+    fn test_spinner_advances_while_processing() -> Result<()> {
+        let mut home = Home::new().spinner_frames(["a", "b", "c"]);
+        assert_eq!(home.spinner_frame(), None);
 
-        let other_rects = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(rect);
+        home.update(Action::EnterProcessing)?;
+        assert_eq!(home.spinner_frame(), Some("a"));
+        home.update(Action::Render)?;
+        assert_eq!(home.spinner_frame(), Some("b"));
+        home.update(Action::Render)?;
+        home.update(Action::Render)?;
+        assert_eq!(home.spinner_frame(), Some("a"));
+        assert!(home.is_dirty());
 
-        let mut text: Vec<Line> = self
-            .text
-            .clone()
-            .iter()
-            .map(|l| Line::from(l.clone()))
-            .collect();
-        text.insert(0, "".into());
-        text.insert(
-            0,
-            "Type into input and hit enter to display here".dim().into(),
-        );
-        text.insert(0, "".into());
-        text.insert(0, format!("Render Ticker: {}", self.render_ticker).into());
-        text.insert(0, format!("App Ticker: {}", self.app_ticker).into());
-        text.insert(0, format!("Counter: {}", self.counter).into());
-        text.insert(0, "".into());
-        text.insert(
-            0,
-            Line::from(vec![
-                "Press ".into(),
-                Span::styled("j", Style::default().fg(Color::Red)),
-                " or ".into(),
-                Span::styled("k", Style::default().fg(Color::Red)),
-                " to ".into(),
-                Span::styled("increment", Style::default().fg(Color::Yellow)),
-                " or ".into(),
-                Span::styled("decrement", Style::default().fg(Color::Yellow)),
-                ".".into(),
-            ]),
+        let mut terminal = Terminal::new(TestBackend::new(40, 10))?;
+        home.update(Action::Render)?;
+        draw(&mut home, &mut terminal);
+        let buf = terminal.backend().buffer();
+        let footer: String = (0..40).map(|x| buf[(x, 9)].symbol()).collect();
+        assert!(footer.starts_with(" PROCESSING 1s  b "), "{footer}");
+
+        home.update(Action::ExitProcessing)?;
+        assert_eq!(home.spinner_frame(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_colon_commands() -> Result<()> {
+        assert_eq!(parse_command("zone add"), Ok(Action::AddZone));
+        assert_eq!(parse_command("help"), Ok(Action::Help));
+        assert_eq!(parse_command(" counter  7 "), Ok(Action::SetCounter(7)));
+        assert!(parse_command("counter x").is_err());
+
+        let mut harness = harness::Harness::new(Home::new())?;
+        harness.home.increment(3);
+        harness.press_keys("/:counter 0<Enter>")?;
+        assert_eq!(harness.home.counter, 0);
+        harness.press_keys("/:foo<Enter>")?;
+        assert_eq!(harness.home.errors, ["Unknown command: :foo"]);
+        harness.press_keys("/:quit<Enter>")?;
+        assert!(harness.actions.contains(&Action::Quit));
+        assert!(harness.home.text_list.is_empty());
+
+        harness.press_keys("/plain<Enter>")?;
+        assert_eq!(harness.home.text_list, ["plain"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_disabled_zones_are_dimmed_and_skipped() -> Result<()> {
+        let mut home = Home::new();
+        home.update(Action::AddZone)?;
+        home.update(Action::AddZone)?;
+        home.update(Action::ToggleZoneEnabled(1))?;
+        assert!(!home.zones[1].enabled);
+
+        home.selected_zone = 0;
+        home.update(Action::NextZone)?;
+        assert_eq!(home.selected_zone, 2);
+        home.update(Action::PrevZone)?;
+        assert_eq!(home.selected_zone, 0);
+
+        let area = Rect::new(0, 0, 30, 8);
+        let mut buf = Buffer::empty(area);
+        let mut state = ZoneWidgetState::default();
+        ZoneWidget::new(&home.zones[1], &home.zones).render_ref(area, &mut buf, &mut state);
+        assert!(buf[(0, 0)].modifier.contains(Modifier::DIM));
+        assert!(buf[(1, 1)].modifier.contains(Modifier::DIM));
+
+        home.update(Action::ToggleZoneEnabled(1))?;
+        assert!(home.zones[1].enabled);
+        let mut buf = Buffer::empty(area);
+        ZoneWidget::new(&home.zones[1], &home.zones).render_ref(area, &mut buf, &mut state);
+        assert!(!buf[(1, 1)].modifier.contains(Modifier::DIM));
+        Ok(())
+    }
+
+    #[test]
+    fn test_zone_edit_rejects_self_and_looping_links() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new())?;
+        harness.home.update(Action::AddZone)?;
+        harness.home.selected_zone = 0;
+        assert_eq!(
+            ZoneWidget::new(&harness.home.zones[0], &harness.home.zones)
+                .value(ZoneItem::UpstreamZone),
+            "<none>"
         );
-        text.insert(0, "".into());
 
-        //f.render_widget(self.selected_tab, rects[0]);
+        harness.home.zone_widget_state.selected = Some(ZoneItem::DownstreamZone);
+        harness.press_keys("<Enter><Backspace>0<Enter>")?;
+        assert_eq!(harness.home.zones[0].next_zone, 1);
+        assert_eq!(
+            harness.home.errors,
+            ["Downstream Zone can't be the zone itself"]
+        );
 
-        f.render_widget(
-            Paragraph::new(text)
-                .block(
-                    Block::default()
-                        .title("ratatui async template")
-                        .title_alignment(Alignment::Center)
-                        .borders(Borders::ALL)
-                        .border_style(match self.mode {
-                            Mode::Processing => Style::default().fg(Color::Yellow),
-                            _ => Style::default(),
-                        })
-                        .border_type(BorderType::Rounded),
-                )
-                .style(Style::default().fg(Color::Cyan))
-                .alignment(Alignment::Center),
-            rects[0],
+        harness.home.selected_zone = 1;
+        harness.press_keys("<Enter><Backspace><Backspace>0<Enter>")?;
+        assert_eq!(harness.home.zones[1].next_zone, -1);
+        assert_eq!(
+            harness.home.errors[1],
+            "Downstream Zone would loop, Zone already links back"
         );
-        let width = rects[1].width.max(3) - 3; // keep 2 for borders and 1 for cursor
-        let scroll = self.input.visual_scroll(width as usize);
-        let input = Paragraph::new(self.input.value())
-            .style(match self.mode {
-                Mode::Insert => Style::default().fg(Color::Yellow),
-                _ => Style::default(),
+
+        // Pointing back the other way is just a link
+        harness.home.zone_widget_state.selected = Some(ZoneItem::UpstreamZone);
+        harness.press_keys("<Enter><Backspace>0<Enter>")?;
+        assert_eq!(harness.home.zones[1].prev_zone, 0);
+        assert_eq!(harness.home.errors.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_zone_chains() {
+        let zone = |name: &str, prev_zone, next_zone| Zone {
+            name: name.to_owned(),
+            prev_zone,
+            next_zone,
+            ..Zone::default()
+        };
+        let zones = [
+            zone("A", -1, 2),
+            zone("B", 2, -1),
+            zone("C", 0, 1),
+            zone("D", -1, -1),
+            zone("E", 5, 5),
+            zone("F", 4, 4),
+        ];
+        assert_eq!(zone_chains(&zones), [vec![0, 2, 1], vec![3], vec![4, 5]]);
+
+        let area = Rect::new(0, 0, 16, 3);
+        let mut buf = Buffer::empty(area);
+        ZoneChainWidget::new(&zones, 2).render(area, &mut buf);
+        let text: Vec<String> = (0..3)
+            .map(|y| {
+                (0..16)
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_owned()
             })
-            .scroll((0, scroll as u16))
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .title(Line::from(vec![
-                        Span::raw("Enter Input Mode "),
-                        Span::styled("(Press ", Style::default().fg(Color::DarkGray)),
-                        Span::styled(
-                            "/",
-                            Style::default()
-                                .add_modifier(Modifier::BOLD)
-                                .fg(Color::Gray),
-                        ),
-                        Span::styled(" to start, ", Style::default().fg(Color::DarkGray)),
-                        Span::styled(
-                            "ESC",
-                            Style::default()
-                                .add_modifier(Modifier::BOLD)
-                                .fg(Color::Gray),
-                        ),
-                        Span::styled(" to finish)", Style::default().fg(Color::DarkGray)),
-                    ])),
-            );
-        f.render_widget(input, rects[1]);
-        if self.mode == Mode::Insert {
-            f.set_cursor_position(Position {
-                x: (rects[1].x + 1 + self.input.cursor() as u16)
-                    .min(rects[1].x + rects[1].width - 2),
-                y: rects[1].y + 1,
+            .collect();
+        assert_eq!(text, ["A → C → B", "D", "E → F ↺"]);
+        assert!(buf[(4, 0)].modifier.contains(Modifier::REVERSED));
+        assert!(!buf[(0, 0)].modifier.contains(Modifier::REVERSED));
+    }
+
+    #[test]
+    fn test_zone_chains_list_merged_zones_once() {
+        let zone = |prev_zone, next_zone| Zone {
+            prev_zone,
+            next_zone,
+            ..Zone::default()
+        };
+        // B is a head that A also links to
+        assert_eq!(zone_chains(&[zone(-1, 1), zone(-1, -1)]), [vec![0, 1]]);
+        // Two heads whose chains meet at C share its tail only once
+        let zones = [zone(-1, 2), zone(-1, 2), zone(0, 3), zone(2, -1)];
+        assert_eq!(zone_chains(&zones), [vec![0, 2, 3], vec![1]]);
+    }
+
+    #[test]
+    fn test_draw_too_small_terminal() {
+        let mut home = Home::new();
+        let mut terminal = Terminal::new(TestBackend::new(10, 5)).unwrap();
+        draw(&mut home, &mut terminal);
+
+        let screen = buffer_string(&terminal);
+        assert!(screen.contains("Terminal"));
+        assert!(screen.contains("20x8"));
+        assert!(!screen.contains("Zones"));
+    }
+
+    #[test]
+    fn test_goto_zone() -> Result<()> {
+        assert_eq!(
+            parse_command("goto Soak  Zone"),
+            Ok(Action::GotoZone("Soak Zone".to_owned()))
+        );
+        assert!(parse_command("goto").is_err());
+
+        let mut harness = harness::Harness::new(Home::new())?;
+        harness.home.zones = ["Preheat", "Soak", "SOAK"]
+            .map(|name| Zone {
+                name: name.to_owned(),
+                ..Zone::default()
             })
+            .into();
+        harness.home.update(Action::GotoZone("Soak".to_owned()))?;
+        assert_eq!(harness.home.selected_zone, 1);
+        harness
+            .home
+            .update(Action::GotoZone("preheat".to_owned()))?;
+        assert_eq!(harness.home.selected_zone, 0);
+        harness.home.update(Action::GotoZone("soak".to_owned()))?;
+        assert_eq!(harness.home.selected_zone, 1);
+
+        harness.press_keys("/:goto kiln<Enter>")?;
+        assert_eq!(harness.home.selected_zone, 1);
+        assert!(
+            harness
+                .actions
+                .contains(&Action::Error("No zone named kiln".to_owned()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_counter_flashes_on_change() -> Result<()> {
+        let mut home = Home::new().flash_ticks(2);
+        home.update(Action::Increment(1))?;
+        assert_eq!(home.last_change, Some(CounterChange::Up));
+        assert_eq!(home.counter_style().fg, Some(Color::Green));
+
+        home.update(Action::Decrement(1))?;
+        assert_eq!(home.last_change, Some(CounterChange::Down));
+        assert_eq!(home.counter_style().fg, Some(Color::Red));
+
+        home.update(Action::Render)?;
+        home.update(Action::Render)?;
+        assert_eq!(home.counter_style(), Style::default());
+        assert_eq!(home.last_change, Some(CounterChange::Down));
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_scheduled_queue() -> Result<()> {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut home = Home::new().schedule_delay(Duration::from_millis(500));
+        home.register_action_handler(tx)?;
+        home.update(Action::ScheduleIncrement)?;
+        home.update(Action::ScheduleDecrement)?;
+        // The decrement replaced the increment
+        assert_eq!(home.scheduled.len(), 1);
+        assert_eq!(home.scheduled[0].id, 2);
+        assert_eq!(home.scheduled[0].action, Action::Decrement(1));
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        home.update(rx.try_recv()?)?;
+        let mut terminal = Terminal::new(TestBackend::new(80, 24))?;
+        draw(&mut home, &mut terminal);
+        assert!(buffer_string(&terminal).contains("#2 Decrement(1) in 0.4s"));
+
+        // An early manual decrement isn't the one in the queue
+        home.update(Action::Decrement(1))?;
+        assert_eq!(home.scheduled.len(), 1);
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        while let Ok(action) = rx.try_recv() {
+            home.update(action)?;
         }
+        assert!(home.scheduled.is_empty());
+        assert_eq!(home.mode(), Mode::Normal);
+        draw(&mut home, &mut terminal);
+        assert!(!buffer_string(&terminal).contains("Queue"));
+        Ok(())
+    }
 
-        if self.show_help {
-            let rect = rect.inner(Margin {
-                horizontal: 4,
-                vertical: 2,
-            });
-            f.render_widget(Clear, rect);
+    #[test]
+    fn test_step() -> Result<()> {
+        let mut home = Home::new().step(5);
+        home.update(Action::IncrementSingle)?;
+        assert_eq!(home.counter, 5);
+        home.pending_count = Some(2);
+        home.update(Action::IncrementSingle)?;
+        assert_eq!(home.counter, 15);
+        home.update(Action::Increment(1))?;
+        assert_eq!(home.counter, 16);
 
-            let block = Block::default()
-                .title(Line::from(vec![Span::styled(
-                    "Key Bindings",
-                    Style::default().add_modifier(Modifier::BOLD),
-                )]))
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Yellow));
-            f.render_widget(block, rect);
-
-            let rows = vec![
-                Row::new(vec!["j", "Increment"]),
-                Row::new(vec!["k", "Decrement"]),
-                Row::new(vec!["/", "Enter Input"]),
-                Row::new(vec!["ESC", "Exit Input"]),
-                Row::new(vec!["Enter", "Submit Input"]),
-                Row::new(vec!["q", "Quit"]),
-                Row::new(vec!["?", "Open Help"]),
-            ];
-
-            let widths = [Constraint::Percentage(10), Constraint::Percentage(90)];
-
-            let table = Table::new(rows, widths)
-                .header(
-                    Row::new(vec!["Key", "Action"])
-                        .bottom_margin(1)
-                        .style(Style::default().add_modifier(Modifier::BOLD)),
-                )
-                //.widths(&[Constraint::Percentage(10), Constraint::Percentage(90)])
-                .column_spacing(1);
-            f.render_widget(
-                table,
-                rect.inner(Margin {
-                    vertical: 4,
-                    horizontal: 2,
-                }),
-            );
-        };
+        home.update(Action::SetStep(0))?;
+        assert_eq!(home.step, 5);
+        home.update(Action::SetStep(10))?;
+        home.update(Action::DecrementSingle)?;
+        assert_eq!(home.counter, 6);
+        Ok(())
+    }
 
-        f.render_widget(
-            Block::default()
-                .title(
-                    Line::from(format!(
-                        "{:?}",
-                        &self
-                            .last_events
-                            .iter()
-                            .map(|k| key_event_to_string(k))
-                            .collect::<Vec<_>>()
-                    ))
-                    .right_aligned(),
-                )
-                .title_style(Style::default().add_modifier(Modifier::BOLD)),
-            Rect {
-                x: rect.x + 1,
-                y: rect.height.saturating_sub(1),
-                width: rect.width.saturating_sub(2),
-                height: 1,
-            },
+    #[test]
+    fn test_read_only() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new())?;
+        harness.press_keys("<Shift-r>")?;
+        assert!(harness.home.read_only);
+
+        harness.press_keys("jj<Ctrl-a>/typed<Enter>")?;
+        assert_eq!(harness.home.counter, 0);
+        assert_eq!(harness.home.zones.len(), 1);
+        assert!(harness.home.text_list.is_empty());
+        assert_eq!(
+            harness
+                .home
+                .message
+                .as_ref()
+                .map(|(_, message, _)| message.as_str()),
+            Some("Read-only, ignoring CompleteInput")
         );
 
-        f.render_stateful_widget_ref(self.selected_tab, rect, &mut ZoneWidgetState {
-            selected: None,
-            selected_mode: ItemMode::Normal,
-        });
+        // Navigation and commands still work
+        harness.press_keys("<Tab>?/:quit<Enter>")?;
+        assert!(harness.home.selected_menu == MenuItem::Sensors);
+        assert!(harness.home.show_help);
+        assert!(harness.actions.contains(&Action::Quit));
+
+        harness.press_keys("<Esc>?<Shift-r>j")?;
+        assert_eq!(harness.home.counter, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_only_writes_nothing() -> Result<()> {
+        let path = temp_path("read-only.json");
+        let mut home = Home::new().load_persisted(&path);
+        home.update(Action::Increment(7))?;
+        home.update(Action::ToggleReadOnly)?;
+        home.update(Action::Save)?;
+        assert_eq!(
+            home.message
+                .as_ref()
+                .map(|(_, message, _)| message.as_str()),
+            Some("Read-only, ignoring Save")
+        );
+        home.update(Action::Quit)?;
+        assert!(!path.exists());
+        Ok(())
+    }
+
+    fn render_zone(state: &mut ZoneWidgetState) -> Buffer {
+        let zone = Zone::default();
+        let area = Rect::new(0, 0, 30, 8);
+        let mut buf = Buffer::empty(area);
+        ZoneWidget::new(&zone, &[]).render_ref(area, &mut buf, state);
+        buf
+    }
+
+    #[test]
+    fn test_snapshot_default_zone() {
+        let buf = render_zone(&mut ZoneWidgetState::default());
+        insta::assert_snapshot!(harness::buffer_to_string(&buf));
+    }
 
-        let list = List::new(self.text_list.clone())
-            .block(Block::bordered().title("Fight!"))
-            .style(Style::new().white())
-            .highlight_style(Color::Blue)
-            .highlight_symbol(">>")
-            .repeat_highlight_symbol(true)
-            .direction(ListDirection::BottomToTop);
-        f.render_stateful_widget(list, other_rects[1], &mut self.text_list_state);
+    #[test]
+    fn test_snapshot_selected_zone() {
+        let mut state = ZoneWidgetState::default();
+        state.select_next();
+        // The selection is only a style, so snapshot the styles too
+        insta::assert_debug_snapshot!(render_zone(&mut state));
+    }
 
+    #[test]
+    fn test_snapshot_help_popup() -> Result<()> {
+        let mut harness = harness::Harness::new(Home::new())?;
+        harness.press_keys("?")?;
+        insta::assert_snapshot!(harness.buffer_string()?);
         Ok(())
     }
 }
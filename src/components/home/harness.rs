@@ -0,0 +1,109 @@
+//! Drives a [`Home`] the way `App` does, without a real terminal.
+
+use color_eyre::eyre::{Result, eyre};
+use crossterm::event::KeyEvent;
+use ratatui::{Terminal, backend::TestBackend, buffer::Buffer};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use super::Home;
+use crate::{
+    action::Action,
+    app,
+    components::Component,
+    config::{Config, parse_key_sequence},
+    tui::Event,
+};
+
+pub struct Harness {
+    pub home: Home,
+    pub terminal: Terminal<TestBackend>,
+    /// Every action `Home` has been sent, in order.
+    pub actions: Vec<Action>,
+    config: Config,
+    tx: UnboundedSender<Action>,
+    rx: UnboundedReceiver<Action>,
+}
+
+impl Harness {
+    /// Wraps `home` with the default config bindings and an 80x24 terminal.
+    pub fn new(mut home: Home) -> Result<Self> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        home.register_action_handler(tx.clone())?;
+        let config = Config::new()?;
+        home.register_config_handler(config.clone())?;
+        Ok(Self {
+            home,
+            terminal: Terminal::new(TestBackend::new(80, 24))?,
+            actions: Vec::new(),
+            config,
+            tx,
+            rx,
+        })
+    }
+
+    /// Lets `Home` handle the key, falling back to the config bindings like the
+    /// app does, and then runs every action that results.
+    pub fn press(&mut self, key: KeyEvent) -> Result<()> {
+        let action = match self.home.handle_events(Some(Event::Key(key)))? {
+            Some(action) => Some(action),
+            None => self
+                .config
+                .keybindings
+                .get(&app::Mode::Home)
+                .and_then(|keymap| keymap.get(&vec![key]))
+                .cloned(),
+        };
+        if let Some(action) = action {
+            self.tx.send(action)?;
+        }
+        while let Ok(action) = self.rx.try_recv() {
+            self.actions.push(action.clone());
+            if let Some(action) = self.home.update(action)? {
+                self.tx.send(action)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Presses each key in `keys`, where plain characters are single keys and
+    /// anything in angle brackets uses the config syntax, e.g. `/abc<Enter>`.
+    ///
+    /// Keys are parsed like config bindings, so letters come through lowercase.
+    pub fn press_keys(&mut self, keys: &str) -> Result<()> {
+        let mut sequence = String::new();
+        let mut in_brackets = false;
+        for c in keys.chars() {
+            match c {
+                '<' => in_brackets = true,
+                '>' => in_brackets = false,
+                c if !in_brackets => sequence.push_str(&format!("<{c}>")),
+                _ => {}
+            }
+            if in_brackets || c == '>' {
+                sequence.push(c);
+            }
+        }
+        for key in parse_key_sequence(&sequence).map_err(|e| eyre!(e))? {
+            self.press(key)?;
+        }
+        Ok(())
+    }
+
+    /// Draws `Home` and returns the screen, one line per row.
+    pub fn buffer_string(&mut self) -> Result<String> {
+        self.terminal.draw(|f| {
+            self.home.draw(f, f.area()).unwrap();
+        })?;
+        Ok(buffer_to_string(self.terminal.backend().buffer()))
+    }
+}
+
+/// The symbols in `buf`, one line per row and without any styling.
+pub fn buffer_to_string(buf: &Buffer) -> String {
+    let rows = (0..buf.area.height).map(|y| {
+        (0..buf.area.width)
+            .map(|x| buf[(buf.area.x + x, buf.area.y + y)].symbol())
+            .collect::<String>()
+    });
+    rows.collect::<Vec<_>>().join("\n")
+}
@@ -0,0 +1,187 @@
+use ratatui::{prelude::*, widgets::*};
+
+#[derive(Clone, Debug, Default)]
+pub struct Sensor {
+    pub name: String,
+    pub value: f64,
+    pub unit: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Motor {
+    pub name: String,
+    pub running: bool,
+    pub speed: i32,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct IoPoint {
+    pub name: String,
+    pub output: bool,
+    pub active: bool,
+}
+
+#[derive(Default)]
+pub struct SensorWidgetState {
+    pub list_state: ListState,
+}
+
+#[derive(Default)]
+pub struct MotorWidgetState {
+    pub list_state: ListState,
+}
+
+#[derive(Default)]
+pub struct IoWidgetState {
+    pub list_state: ListState,
+}
+
+#[derive(Default)]
+pub struct MiscWidgetState {
+    pub table_state: TableState,
+}
+
+/// Renders `rows` as a list inside `block`, titled with the number of rows.
+fn render_list(
+    block: &Block<'_>,
+    rows: Vec<String>,
+    empty: &str,
+    area: Rect,
+    buf: &mut Buffer,
+    state: &mut ListState,
+) {
+    let block = block
+        .clone()
+        .title(Line::from(format!(" {} ", rows.len())).right_aligned());
+    if rows.is_empty() {
+        Paragraph::new(empty.dim()).block(block).render(area, buf);
+        return;
+    }
+    let list = List::new(rows).block(block).highlight_symbol(">> ");
+    StatefulWidgetRef::render_ref(&list, area, buf, state);
+}
+
+pub struct SensorWidget<'a> {
+    sensors: &'a [Sensor],
+    block: Block<'a>,
+}
+
+impl<'a> SensorWidget<'a> {
+    pub fn new(sensors: &'a [Sensor], block: Block<'a>) -> Self {
+        Self { sensors, block }
+    }
+}
+
+impl StatefulWidgetRef for SensorWidget<'_> {
+    type State = SensorWidgetState;
+
+    fn render_ref(&self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let rows = self
+            .sensors
+            .iter()
+            .map(|s| format!("{}: {:.2} {}", s.name, s.value, s.unit))
+            .collect();
+        render_list(
+            &self.block,
+            rows,
+            "No sensors configured",
+            area,
+            buf,
+            &mut state.list_state,
+        );
+    }
+}
+
+pub struct MotorWidget<'a> {
+    motors: &'a [Motor],
+    block: Block<'a>,
+}
+
+impl<'a> MotorWidget<'a> {
+    pub fn new(motors: &'a [Motor], block: Block<'a>) -> Self {
+        Self { motors, block }
+    }
+}
+
+impl StatefulWidgetRef for MotorWidget<'_> {
+    type State = MotorWidgetState;
+
+    fn render_ref(&self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let rows = self
+            .motors
+            .iter()
+            .map(|m| {
+                let status = if m.running { "running" } else { "stopped" };
+                format!("{}: {} @ {}", m.name, status, m.speed)
+            })
+            .collect();
+        render_list(
+            &self.block,
+            rows,
+            "No motors configured",
+            area,
+            buf,
+            &mut state.list_state,
+        );
+    }
+}
+
+pub struct IoWidget<'a> {
+    points: &'a [IoPoint],
+    block: Block<'a>,
+}
+
+impl<'a> IoWidget<'a> {
+    pub fn new(points: &'a [IoPoint], block: Block<'a>) -> Self {
+        Self { points, block }
+    }
+}
+
+impl StatefulWidgetRef for IoWidget<'_> {
+    type State = IoWidgetState;
+
+    fn render_ref(&self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let rows = self
+            .points
+            .iter()
+            .map(|p| {
+                let direction = if p.output { "out" } else { "in" };
+                let level = if p.active { "on" } else { "off" };
+                format!("{} ({}): {}", p.name, direction, level)
+            })
+            .collect();
+        render_list(
+            &self.block,
+            rows,
+            "No I/O points configured",
+            area,
+            buf,
+            &mut state.list_state,
+        );
+    }
+}
+
+pub struct MiscWidget<'a> {
+    entries: Vec<(&'a str, String)>,
+    block: Block<'a>,
+}
+
+impl<'a> MiscWidget<'a> {
+    pub fn new(entries: Vec<(&'a str, String)>, block: Block<'a>) -> Self {
+        Self { entries, block }
+    }
+}
+
+impl StatefulWidgetRef for MiscWidget<'_> {
+    type State = MiscWidgetState;
+
+    fn render_ref(&self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let rows = self
+            .entries
+            .iter()
+            .map(|(key, value)| Row::new(vec![key.to_string(), value.clone()]));
+        let table = Table::new(rows, [Constraint::Length(16), Constraint::Fill(1)])
+            .block(self.block.clone());
+        StatefulWidgetRef::render_ref(&table, area, buf, &mut state.table_state);
+    }
+}
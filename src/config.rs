@@ -1,15 +1,19 @@
-use std::{collections::HashMap, fmt, path::PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
-use color_eyre::eyre::Result;
-use config::Value;
+use color_eyre::eyre::{Result, eyre};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use derive_deref::{Deref, DerefMut};
-use ratatui::style::{Color, Modifier, Style};
+use ratatui::style::{
+    Color, Modifier, Style,
+    palette::tailwind::{self, Palette},
+};
 use serde::{
-    Deserialize, Serialize,
-    de::{self, Deserializer, MapAccess, Visitor},
+    Deserialize,
+    de::{Deserializer, Error as _, IntoDeserializer, value::StrDeserializer},
 };
-use serde_json::Value as JsonValue;
 
 use crate::{action::Action, app::Mode};
 
@@ -31,6 +35,8 @@ pub struct Config {
     pub keybindings: KeyBindings,
     #[serde(default)]
     pub styles: Styles,
+    #[serde(default)]
+    pub themes: Vec<Theme>,
 }
 
 impl Config {
@@ -79,14 +85,141 @@ impl Config {
             for (style_key, style) in default_styles.iter() {
                 user_styles
                     .entry(style_key.clone())
-                    .or_insert_with(|| style.clone());
+                    .or_insert_with(|| *style);
             }
         }
 
+        if cfg.themes.is_empty() {
+            cfg.themes = default_config.themes;
+        }
+
         Ok(cfg)
     }
 }
 
+/// Colors for the UI; `Action::CycleTheme` switches between the configured ones.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Theme {
+    pub name: String,
+    /// Name of the tailwind palette of each tab, in tab order.
+    #[serde(deserialize_with = "deserialize_palettes")]
+    pub tabs: Vec<String>,
+    /// Border of popups such as the help and the quit dialog.
+    pub border: Color,
+    /// Highlighted history entry.
+    pub highlight: Color,
+    /// Selected zone item, as a style string like `"bold black on cyan"`.
+    #[serde(
+        default = "default_zone_highlight",
+        deserialize_with = "deserialize_style"
+    )]
+    pub zone_highlight: Style,
+    /// Highlighted history entry while another pane has focus, as a style string.
+    #[serde(
+        default = "default_muted_highlight",
+        deserialize_with = "deserialize_style"
+    )]
+    pub muted_highlight: Style,
+    /// Text of the main panel.
+    #[serde(default = "default_text")]
+    pub text: Color,
+    pub modes: ModeColors,
+}
+
+fn default_text() -> Color {
+    Color::Cyan
+}
+
+fn default_zone_highlight() -> Style {
+    Style::new().add_modifier(Modifier::REVERSED)
+}
+
+fn default_muted_highlight() -> Style {
+    Style::new().fg(Color::DarkGray)
+}
+
+fn deserialize_style<'de, D>(deserializer: D) -> Result<Style, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(parse_style(&String::deserialize(deserializer)?))
+}
+
+/// Footer badge color for each input mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub struct ModeColors {
+    pub normal: Color,
+    pub insert: Color,
+    pub filter: Color,
+    pub processing: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            name: "Tailwind".to_owned(),
+            tabs: ["blue", "red", "green", "amber", "purple"]
+                .map(String::from)
+                .to_vec(),
+            border: Color::Yellow,
+            highlight: Color::Blue,
+            zone_highlight: default_zone_highlight(),
+            muted_highlight: default_muted_highlight(),
+            text: default_text(),
+            modes: ModeColors {
+                normal: Color::Blue,
+                insert: Color::Yellow,
+                filter: Color::Magenta,
+                processing: Color::Red,
+            },
+        }
+    }
+}
+
+/// Checks that every tab palette name is one `parse_palette` knows.
+fn deserialize_palettes<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let names = Vec::<String>::deserialize(deserializer)?;
+    if let Some(name) = names.iter().find(|name| parse_palette(name).is_none()) {
+        return Err(serde::de::Error::custom(format!(
+            "Unknown palette `{name}`"
+        )));
+    }
+    Ok(names)
+}
+
+/// Looks up a tailwind palette by its lowercase name, e.g. `"amber"`.
+pub fn parse_palette(name: &str) -> Option<Palette> {
+    let palette = match name.to_lowercase().as_str() {
+        "slate" => tailwind::SLATE,
+        "gray" => tailwind::GRAY,
+        "zinc" => tailwind::ZINC,
+        "neutral" => tailwind::NEUTRAL,
+        "stone" => tailwind::STONE,
+        "red" => tailwind::RED,
+        "orange" => tailwind::ORANGE,
+        "amber" => tailwind::AMBER,
+        "yellow" => tailwind::YELLOW,
+        "lime" => tailwind::LIME,
+        "green" => tailwind::GREEN,
+        "emerald" => tailwind::EMERALD,
+        "teal" => tailwind::TEAL,
+        "cyan" => tailwind::CYAN,
+        "sky" => tailwind::SKY,
+        "blue" => tailwind::BLUE,
+        "indigo" => tailwind::INDIGO,
+        "violet" => tailwind::VIOLET,
+        "purple" => tailwind::PURPLE,
+        "fuchsia" => tailwind::FUCHSIA,
+        "pink" => tailwind::PINK,
+        "rose" => tailwind::ROSE,
+        _ => return None,
+    };
+    Some(palette)
+}
+
 #[derive(Clone, Debug, Default, Deref, DerefMut)]
 pub struct KeyBindings(pub HashMap<Mode, HashMap<Vec<KeyEvent>, Action>>);
 
@@ -100,19 +233,116 @@ impl<'de> Deserialize<'de> for KeyBindings {
         let keybindings = parsed_map
             .into_iter()
             .map(|(mode, inner_map)| {
-                let converted_inner_map = inner_map
+                let converted_bindings: Vec<(Vec<KeyEvent>, Action)> = inner_map
                     .into_iter()
                     .map(|(key_str, cmd)| (parse_key_sequence(&key_str).unwrap(), cmd))
                     .collect();
-                (mode, converted_inner_map)
+                // Spellings like `<ctrl-s>` and `<Ctrl-S>` are the same key, so bind
+                // single keys through a builder rather than keep one of them at random
+                converted_bindings
+                    .iter()
+                    .filter_map(|(keys, cmd)| match keys.as_slice() {
+                        [key] => Some((*key, cmd.clone())),
+                        _ => None,
+                    })
+                    .fold(KeymapBuilder::new(), |keymap, (key, cmd)| {
+                        keymap.bind(key, cmd)
+                    })
+                    .build()
+                    .map_err(D::Error::custom)?;
+                Ok((mode, converted_bindings.into_iter().collect()))
             })
-            .collect();
+            .collect::<Result<_, D::Error>>()?;
 
         Ok(KeyBindings(keybindings))
     }
 }
 
-fn parse_key_event(raw: &str) -> Result<KeyEvent, String> {
+/// Reads a file of key to action name bindings, like `{ "ctrl-s": "Save" }`, for
+/// `Home::keymap`. The format follows the extension, so TOML, JSON and JSON5 all work.
+pub fn load_keymap(path: impl AsRef<Path>) -> Result<HashMap<KeyEvent, Action>> {
+    let bindings: HashMap<String, String> = config::Config::builder()
+        .add_source(config::File::from(path.as_ref()))
+        .build()?
+        .try_deserialize()?;
+    let mut keymap = KeymapBuilder::new();
+    for (key, name) in bindings {
+        let event = parse_key_event(&key).map_err(|e| eyre!("{e} in the keymap"))?;
+        let deserializer: StrDeserializer<'_, serde::de::value::Error> =
+            name.as_str().into_deserializer();
+        let action = Action::deserialize(deserializer)
+            .map_err(|_| eyre!("Unknown action `{name}` bound to `{key}`"))?;
+        keymap = keymap.bind(event, action);
+    }
+    keymap.build()
+}
+
+/// File in the config directory whose bindings, read with [`load_keymap`], take
+/// precedence over the `Home` bindings of the config.
+pub const KEYMAP_FILE: &str = "keymap.json5";
+
+/// The bindings of [`KEYMAP_FILE`] in `config_dir`, or none if there's no such file.
+pub fn load_user_keymap(config_dir: &Path) -> Result<HashMap<KeyEvent, Action>> {
+    let path = config_dir.join(KEYMAP_FILE);
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    load_keymap(path)
+}
+
+/// Collects key bindings, refusing to build a keymap where one key has two actions.
+#[derive(Debug, Default)]
+pub struct KeymapBuilder {
+    bindings: Vec<(KeyEvent, Action)>,
+}
+
+impl KeymapBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn bind(mut self, key: KeyEvent, action: Action) -> Self {
+        self.bindings.push((key, action));
+        self
+    }
+
+    /// The keymap, or an error listing every key that was bound to different actions.
+    pub fn build(self) -> Result<HashMap<KeyEvent, Action>> {
+        let mut keymap: HashMap<KeyEvent, Action> = HashMap::new();
+        let mut conflicts: Vec<(KeyEvent, Vec<Action>)> = Vec::new();
+        for (key, action) in self.bindings {
+            match keymap.get(&key) {
+                Some(bound) if *bound == action => {}
+                Some(bound) => match conflicts.iter_mut().find(|(k, _)| *k == key) {
+                    Some((_, actions)) if actions.contains(&action) => {}
+                    Some((_, actions)) => actions.push(action),
+                    None => conflicts.push((key, vec![bound.clone(), action])),
+                },
+                None => {
+                    keymap.insert(key, action);
+                }
+            }
+        }
+        if conflicts.is_empty() {
+            return Ok(keymap);
+        }
+        let conflicts = conflicts
+            .iter()
+            .map(|(key, actions)| {
+                let actions = actions.iter().map(Action::to_string).collect::<Vec<_>>();
+                format!(
+                    "`{}` is bound to {}",
+                    key_event_to_string(key),
+                    actions.join(" and ")
+                )
+            })
+            .collect::<Vec<_>>();
+        Err(eyre!("Conflicting key bindings: {}", conflicts.join(", ")))
+    }
+}
+
+/// Parses a single key like `ctrl-s` or `shift-tab`, the inverse of [`key_event_to_string`].
+pub fn parse_key_event(raw: &str) -> Result<KeyEvent, String> {
     let raw_lower = raw.to_ascii_lowercase();
     let (remaining, modifiers) = extract_modifiers(&raw_lower);
     parse_key_code_with_modifiers(remaining, modifiers)
@@ -211,10 +441,10 @@ pub fn key_event_to_string(key_event: &KeyEvent) -> String {
         KeyCode::Delete => "delete",
         KeyCode::Insert => "insert",
         KeyCode::F(c) => {
-            char = format!("f({c})");
+            char = format!("f{c}");
             &char
         }
-        KeyCode::Char(c) if c == ' ' => "space",
+        KeyCode::Char(' ') => "space",
         KeyCode::Char(c) => {
             char = c.to_string();
             &char
@@ -262,8 +492,7 @@ pub fn parse_key_sequence(raw: &str) -> Result<Vec<KeyEvent>, String> {
     }
     let raw = if !raw.contains("><") {
         let raw = raw.strip_prefix('<').unwrap_or(raw);
-        let raw = raw.strip_prefix('>').unwrap_or(raw);
-        raw
+        raw.strip_prefix('>').unwrap_or(raw)
     } else {
         raw
     };
@@ -455,7 +684,7 @@ mod tests {
     #[test]
     fn test_parse_color_rgb() {
         let color = parse_color("rgb123");
-        let expected = 16 + 1 * 36 + 2 * 6 + 3;
+        let expected = 16 + 36 + 2 * 6 + 3;
         assert_eq!(color, Some(Color::Indexed(expected)));
     }
 
@@ -474,11 +703,61 @@ mod tests {
                 .unwrap()
                 .get(&parse_key_sequence("<q>").unwrap_or_default())
                 .unwrap(),
-            &Action::Quit
+            &Action::RequestQuit
+        );
+        assert_eq!(
+            c.keybindings
+                .get(&Mode::Home)
+                .unwrap()
+                .get(&parse_key_sequence("<Ctrl-c>").unwrap_or_default())
+                .unwrap(),
+            &Action::RequestQuit
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_conflicting_keybindings() {
+        let result: Result<KeyBindings, _> =
+            json5::from_str(r#"{ "Home": { "<ctrl-s>": "Save", "<Ctrl-S>": "Quit" } }"#);
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("`ctrl-s` is bound to"), "{error}");
+
+        let result: Result<KeyBindings, _> =
+            json5::from_str(r#"{ "Home": { "<ctrl-s>": "Save", "<Ctrl-S>": "Save" } }"#);
+        assert_eq!(result.unwrap()[&Mode::Home].len(), 1);
+    }
+
+    #[test]
+    fn test_default_themes() -> Result<()> {
+        let c = Config::new()?;
+        assert_eq!(c.themes[0], Theme::default());
+        assert_eq!(c.themes[1].name, "Ocean");
+        assert_eq!(c.themes[1].border, Color::LightCyan);
+        assert_eq!(c.themes[1].text, Color::White);
+        assert_eq!(
+            c.themes[1].zone_highlight,
+            Style::new()
+                .fg(Color::Indexed(0))
+                .bg(Color::Indexed(6))
+                .add_modifier(Modifier::BOLD)
         );
         Ok(())
     }
 
+    #[test]
+    fn test_unknown_palette() {
+        let theme = r#"{
+            "name": "Bad",
+            "tabs": ["blue", "mauve"],
+            "border": "yellow",
+            "highlight": "blue",
+            "modes": { "normal": "blue", "insert": "yellow", "filter": "magenta", "processing": "red" }
+        }"#;
+        let err = json5::from_str::<Theme>(theme).unwrap_err();
+        assert!(err.to_string().contains("Unknown palette `mauve`"));
+    }
+
     #[test]
     fn test_simple_keys() {
         assert_eq!(
@@ -542,6 +821,94 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_key_event_round_trip() {
+        let keys = [
+            KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL),
+            KeyEvent::new(KeyCode::Char('J'), KeyModifiers::SHIFT),
+            KeyEvent::new(KeyCode::Char('-'), KeyModifiers::ALT),
+            KeyEvent::new(KeyCode::Char(' '), KeyModifiers::empty()),
+            KeyEvent::new(KeyCode::Tab, KeyModifiers::SHIFT),
+            KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT),
+            KeyEvent::new(KeyCode::Esc, KeyModifiers::empty()),
+            KeyEvent::new(KeyCode::Enter, KeyModifiers::CONTROL | KeyModifiers::ALT),
+            KeyEvent::new(KeyCode::F(1), KeyModifiers::empty()),
+            KeyEvent::new(KeyCode::F(12), KeyModifiers::CONTROL),
+            KeyEvent::new(KeyCode::Up, KeyModifiers::empty()),
+            KeyEvent::new(KeyCode::PageDown, KeyModifiers::SHIFT),
+        ];
+        for key in keys {
+            let raw = key_event_to_string(&key);
+            assert_eq!(parse_key_event(&raw), Ok(key), "{raw}");
+        }
+    }
+
+    #[test]
+    fn test_load_keymap() -> Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("hello-ratatui-{}-keymap.toml", std::process::id()));
+        std::fs::write(&path, "\"ctrl-s\" = \"Save\"\nq = \"Quit\"\n")?;
+        let keymap = load_keymap(&path)?;
+        assert_eq!(
+            keymap,
+            HashMap::from([
+                (
+                    KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL),
+                    Action::Save
+                ),
+                (KeyEvent::from(KeyCode::Char('q')), Action::Quit),
+            ])
+        );
+
+        std::fs::write(&path, "j = \"Jump\"\n")?;
+        let error = load_keymap(&path).unwrap_err();
+        assert_eq!(error.to_string(), "Unknown action `Jump` bound to `j`");
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_user_keymap() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("hello-ratatui-{}-config", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        assert!(load_user_keymap(&dir)?.is_empty());
+
+        std::fs::write(dir.join(KEYMAP_FILE), "{ \"ctrl-s\": \"Save\" }")?;
+        assert_eq!(
+            load_user_keymap(&dir)?,
+            HashMap::from([(
+                KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL),
+                Action::Save
+            )])
+        );
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_keymap_builder_conflicts() {
+        let j = KeyEvent::from(KeyCode::Char('j'));
+        let k = KeyEvent::from(KeyCode::Char('k'));
+        let keymap = KeymapBuilder::new()
+            .bind(j, Action::NextZone)
+            .bind(k, Action::PrevZone)
+            .bind(j, Action::NextZone)
+            .build()
+            .unwrap();
+        assert_eq!(keymap.len(), 2);
+
+        let error = KeymapBuilder::new()
+            .bind(j, Action::NextZone)
+            .bind(k, Action::PrevZone)
+            .bind(j, Action::Quit)
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Conflicting key bindings: `j` is bound to NextZone and Quit"
+        );
+    }
+
     #[test]
     fn test_invalid_keys() {
         assert!(parse_key_event("invalid-key").is_err());
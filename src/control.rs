@@ -0,0 +1,137 @@
+//! A Unix socket that lets other programs drive the app.
+//!
+//! Each line written to the socket is an `Action` in JSON, such as `"Quit"` or
+//! `{"Increment":3}`, and is handled as if it came from a key binding.
+
+use std::{
+    io::ErrorKind,
+    os::unix::fs::FileTypeExt,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::{Result, eyre};
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    net::{UnixListener, UnixStream},
+    sync::mpsc::UnboundedSender,
+    task::JoinHandle,
+};
+
+use crate::action::Action;
+
+/// Reads one line sent to the control socket, turning anything that isn't an
+/// action into an `Action::Error` saying so.
+pub fn parse_line(line: &str) -> Action {
+    serde_json::from_str(line)
+        .unwrap_or_else(|e| Action::Error(format!("Bad control command {line:?}: {e}")))
+}
+
+/// A listening control socket, which stops and removes its file when dropped.
+pub struct ControlSocket {
+    path: PathBuf,
+    task: JoinHandle<()>,
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        self.task.abort();
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            log::error!("Failed to remove {}: {e}", self.path.display());
+        }
+    }
+}
+
+/// Listens on a Unix socket at `path`, forwarding the actions sent to it to `tx`
+/// until the app stops receiving them or the returned socket is dropped.
+///
+/// A socket left behind at `path` by an earlier run is replaced, but anything
+/// else already there is an error rather than being deleted.
+pub fn listen(path: &Path, tx: UnboundedSender<Action>) -> Result<ControlSocket> {
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path)?,
+        Ok(_) => {
+            return Err(eyre!(
+                "Not a socket, refusing to replace it: {}",
+                path.display()
+            ));
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+    }
+    let listener = UnixListener::bind(path)?;
+    let task = tokio::spawn(async move {
+        loop {
+            let accepted = tokio::select! {
+                _ = tx.closed() => break,
+                accepted = listener.accept() => accepted,
+            };
+            match accepted {
+                Ok((stream, _)) => {
+                    tokio::spawn(forward(stream, tx.clone()));
+                }
+                Err(e) => log::error!("Control socket failed: {e}"),
+            }
+        }
+    });
+    Ok(ControlSocket {
+        path: path.to_owned(),
+        task,
+    })
+}
+
+async fn forward(stream: UnixStream, tx: UnboundedSender<Action>) {
+    let mut lines = BufReader::new(stream).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if tx.send(parse_line(&line)).is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tokio::io::AsyncWriteExt;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(parse_line(r#"{"Increment":3}"#), Action::Increment(3));
+        assert_eq!(parse_line(r#""Quit""#), Action::Quit);
+        assert!(matches!(parse_line("Increment(3)"), Action::Error(_)));
+    }
+
+    #[tokio::test]
+    async fn test_listen_forwards_actions() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("control-{}.sock", std::process::id()));
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let listener = listen(&path, tx)?;
+
+        let mut stream = UnixStream::connect(&path).await?;
+        stream
+            .write_all(b"{\"Increment\":3}\n\nnonsense\n\"Quit\"\n")
+            .await?;
+        assert_eq!(rx.recv().await, Some(Action::Increment(3)));
+        assert!(matches!(rx.recv().await, Some(Action::Error(_))));
+        assert_eq!(rx.recv().await, Some(Action::Quit));
+
+        drop(stream);
+        drop(listener);
+        assert!(!path.exists(), "the socket is removed once dropped");
+
+        // A stale socket is replaced, anything else is left alone
+        let stale = std::os::unix::net::UnixListener::bind(&path)?;
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        drop(listen(&path, tx.clone())?);
+        drop(stale);
+        std::fs::write(&path, "not a socket")?;
+        assert!(listen(&path, tx).is_err());
+        assert_eq!(std::fs::read_to_string(&path)?, "not a socket");
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
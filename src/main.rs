@@ -5,6 +5,8 @@ pub mod app;
 pub mod cli;
 pub mod components;
 pub mod config;
+#[cfg(feature = "control-socket")]
+pub mod control;
 pub mod tui;
 pub mod utils;
 
@@ -23,7 +25,11 @@ async fn tokio_main() -> Result<()> {
     initialize_panic_handler()?;
 
     let args = Cli::parse();
-    let mut app = App::new(args.tick_rate, args.frame_rate)?;
+    let mut app = App::new(args.tick_rate, args.frame_rate)?.coalesce_actions(true);
+    #[cfg(feature = "control-socket")]
+    if let Some(path) = args.control_socket {
+        app = app.control_socket(path);
+    }
     app.run().await?;
 
     Ok(())
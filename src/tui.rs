@@ -6,7 +6,10 @@ use std::{
 use color_eyre::eyre::Result;
 use crossterm::{
     cursor,
-    event::{Event as CrosstermEvent, KeyEvent, KeyEventKind, MouseEvent},
+    event::{
+        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event as CrosstermEvent, KeyEvent, KeyEventKind, MouseEvent,
+    },
     terminal::{EnterAlternateScreen, LeaveAlternateScreen},
 };
 use futures::{FutureExt, StreamExt};
@@ -44,6 +47,8 @@ pub struct Tui {
     pub event_tx: UnboundedSender<Event>,
     pub frame_rate: f64,
     pub tick_rate: f64,
+    pub mouse: bool,
+    pub paste: bool,
 }
 
 impl Tui {
@@ -62,6 +67,8 @@ impl Tui {
             event_tx,
             frame_rate,
             tick_rate,
+            mouse: false,
+            paste: false,
         })
     }
 
@@ -73,6 +80,14 @@ impl Tui {
         self.frame_rate = frame_rate;
     }
 
+    pub fn mouse(&mut self, mouse: bool) {
+        self.mouse = mouse;
+    }
+
+    pub fn paste(&mut self, paste: bool) {
+        self.paste = paste;
+    }
+
     pub fn start(&mut self) {
         let tick_delay = std::time::Duration::from_secs_f64(1.0 / self.tick_rate);
         let render_delay = std::time::Duration::from_secs_f64(1.0 / self.frame_rate);
@@ -84,12 +99,14 @@ impl Tui {
             let mut reader = crossterm::event::EventStream::new();
             let mut tick_interval = tokio::time::interval(tick_delay);
             let mut render_interval = tokio::time::interval(render_delay);
-            _event_tx.send(Event::Init).unwrap();
+            if _event_tx.send(Event::Init).is_err() {
+                return;
+            }
             loop {
                 let tick_delay = tick_interval.tick();
                 let render_delay = render_interval.tick();
                 let crossterm_event = reader.next().fuse();
-                tokio::select! {
+                let event = tokio::select! {
                   _ = _cancellation_token.cancelled() => {
                     break;
                   }
@@ -97,40 +114,25 @@ impl Tui {
                     match maybe_event {
                       Some(Ok(evt)) => {
                         match evt {
-                          CrosstermEvent::Key(key) => {
-                            if key.kind == KeyEventKind::Press {
-                              _event_tx.send(Event::Key(key)).unwrap();
-                            }
-                          },
-                          CrosstermEvent::Mouse(mouse) => {
-                            _event_tx.send(Event::Mouse(mouse)).unwrap();
-                          },
-                          CrosstermEvent::Resize(x, y) => {
-                            _event_tx.send(Event::Resize(x, y)).unwrap();
-                          },
-                          CrosstermEvent::FocusLost => {
-                            _event_tx.send(Event::FocusLost).unwrap();
-                          },
-                          CrosstermEvent::FocusGained => {
-                            _event_tx.send(Event::FocusGained).unwrap();
-                          },
-                          CrosstermEvent::Paste(s) => {
-                            _event_tx.send(Event::Paste(s)).unwrap();
-                          },
+                          CrosstermEvent::Key(key) if key.kind == KeyEventKind::Press => Event::Key(key),
+                          CrosstermEvent::Key(_) => continue,
+                          CrosstermEvent::Mouse(mouse) => Event::Mouse(mouse),
+                          CrosstermEvent::Resize(x, y) => Event::Resize(x, y),
+                          CrosstermEvent::FocusLost => Event::FocusLost,
+                          CrosstermEvent::FocusGained => Event::FocusGained,
+                          CrosstermEvent::Paste(s) => Event::Paste(s),
                         }
                       }
-                      Some(Err(_)) => {
-                        _event_tx.send(Event::Error).unwrap();
-                      }
-                      None => {},
+                      Some(Err(_)) => Event::Error,
+                      None => continue,
                     }
                   },
-                  _ = tick_delay => {
-                      _event_tx.send(Event::Tick).unwrap();
-                  },
-                  _ = render_delay => {
-                      _event_tx.send(Event::Render).unwrap();
-                  },
+                  _ = tick_delay => Event::Tick,
+                  _ = render_delay => Event::Render,
+                };
+                // The app has stopped listening while shutting down, so stop with it
+                if _event_tx.send(event).is_err() {
+                    break;
                 }
             }
         });
@@ -156,6 +158,12 @@ impl Tui {
     pub fn enter(&mut self) -> Result<()> {
         crossterm::terminal::enable_raw_mode()?;
         crossterm::execute!(std::io::stderr(), EnterAlternateScreen, cursor::Hide)?;
+        if self.mouse {
+            crossterm::execute!(std::io::stderr(), EnableMouseCapture)?;
+        }
+        if self.paste {
+            crossterm::execute!(std::io::stderr(), EnableBracketedPaste)?;
+        }
         self.start();
         Ok(())
     }
@@ -164,6 +172,12 @@ impl Tui {
         self.stop()?;
         if crossterm::terminal::is_raw_mode_enabled()? {
             self.flush()?;
+            if self.paste {
+                crossterm::execute!(std::io::stderr(), DisableBracketedPaste)?;
+            }
+            if self.mouse {
+                crossterm::execute!(std::io::stderr(), DisableMouseCapture)?;
+            }
             crossterm::execute!(std::io::stderr(), LeaveAlternateScreen, cursor::Show)?;
             crossterm::terminal::disable_raw_mode()?;
         }